@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-use crate::{AppState, ASSET_PATH};
+use crate::{ASSET_PATH, AppState};
 use std::{
     collections::HashMap,
     fs::File,
@@ -9,6 +9,8 @@ use std::{
     sync::LazyLock,
 };
 
+// `ASSET_PATH` is a `LazyLock<String>` in release builds, so the deref is not redundant there.
+#[allow(clippy::borrow_deref_ref)]
 pub static DOC_PATH: LazyLock<String> = LazyLock::new(|| format!("{}/commands.json", &*ASSET_PATH));
 
 pub struct CommandRegistry {
@@ -61,9 +63,11 @@ pub enum CommandReturns {
     None,
 }
 
+type EnvModifier = Box<dyn Fn(&mut AppState, &CommandRegistry) -> CommandReturns>;
+
 pub struct Command {
     id: String,
-    env_modifier: Box<dyn Fn(&mut AppState, &CommandRegistry) -> CommandReturns>,
+    env_modifier: EnvModifier,
 }
 
 impl Command {
@@ -77,7 +81,7 @@ impl Command {
         }
     }
 
-    pub fn get_doc<'s, 'r>(&'s self, reg: &'r CommandRegistry) -> Option<&'r String> {
+    pub fn get_doc<'r>(&self, reg: &'r CommandRegistry) -> Option<&'r String> {
         reg.command_meta.get(&self.id).map(|meta| &meta.doc)
     }
 }
@@ -85,15 +89,15 @@ impl Command {
 pub(super) fn register_default_commands(reg: &mut CommandRegistry) {
     let exit_command = Command::new("exit", |_, _| CommandReturns::Exit);
     let clr_env_cmd = Command::new("clr-env", |state, _| {
-        state.env.store.clear();
+        state.env.store.lock().unwrap().clear();
         CommandReturns::None
     });
 
     let help_command = Command::new("help", |state, reg| {
         for command in &reg.commands {
-            let _ = write!(
+            let _ = writeln!(
                 state.stdout,
-                "{}: {}\n",
+                "{}: {}",
                 command.id,
                 command.get_doc(reg).unwrap_or(&"N/A".into())
             );
@@ -1,8 +1,13 @@
 mod command;
+mod manifest;
 
-use std::io::{self, Stderr, Stdin, Stdout, Write};
+use std::{
+    io::{self, Stderr, Stdin, Stdout, Write},
+    path::Path,
+};
 
-use command::{register_default_commands, CommandRegistry};
+use command::{CommandRegistry, register_default_commands};
+use manifest::{MANIFEST_FILE, Project};
 use spool::{Env, Parsed};
 
 const PROMPT: &str = "→ ";
@@ -19,20 +24,34 @@ pub const ASSET_PATH: &str = "../../assets/";
 #[cfg(target_os = "windows")]
 const ASSET_PATH: &str = compile_error!("Windows is not supported yet");
 
-pub(crate) struct AppState<'e> {
+pub(crate) struct AppState {
     pub(crate) stdin: Stdin,
     pub(crate) stdout: Stdout,
     pub(crate) stderr: Stderr,
-    pub(crate) env: Env<'e>,
+    pub(crate) env: Env,
 }
 
 fn main() -> io::Result<()> {
+    if let Some(path) = std::env::args().nth(1) {
+        return run(Path::new(&path));
+    }
+
     let mut app_state = AppState {
         stdin: io::stdin(),
         stdout: io::stdout(),
         stderr: io::stderr(),
         env: Env::new(),
     };
+    // the CLI runs the user's own scripts, so they may use files
+    app_state.env.allow_file_access();
+
+    // the dependencies of the package in the working directory can be imported from the REPL
+    if Path::new(MANIFEST_FILE).exists() {
+        match Project::resolve(Path::new(".")) {
+            Ok(project) => project.add_packages(&mut app_state.env),
+            Err(e) => writeln!(app_state.stderr, "Manifest error: {e}")?,
+        }
+    }
 
     let mut command_registry = CommandRegistry::new()?;
 
@@ -61,17 +80,14 @@ fn main() -> io::Result<()> {
         let (s, parsed) = match Parsed::parse(&input) {
             Ok(v) => v,
             Err(e) => {
-                write!(app_state.stderr, "Parse error: {:?}\n", e)?;
+                writeln!(app_state.stderr, "Parse error: {:?}", e)?;
                 app_state.stderr.flush()?;
                 continue;
             }
         };
 
         if !s.is_empty() {
-            write!(
-                app_state.stderr,
-                "Input not fully consumed by parser: {s}\n"
-            )?;
+            writeln!(app_state.stderr, "Input not fully consumed by parser: {s}")?;
             app_state.stderr.flush()?;
             continue;
         }
@@ -79,26 +95,65 @@ fn main() -> io::Result<()> {
         let eval = match parsed.eval(&mut app_state.env) {
             Ok(v) => v,
             Err(e) => {
-                write!(app_state.stderr, "Evaluation error: {:?}\n", e)?;
+                writeln!(app_state.stderr, "Evaluation error: {:?}", e)?;
                 app_state.stderr.flush()?;
                 continue;
             }
         };
 
-        write!(app_state.stdout, "{:?}\n", eval)?;
+        writeln!(app_state.stdout, "{:?}", eval)?;
         app_state.stdout.flush()?;
     }
 }
 
+/// Runs the file at `path`, or the entry file of the package in the directory at `path`.
+fn run(path: &Path) -> io::Result<()> {
+    let (file, mut env) = if path.is_dir() {
+        match Project::resolve(path) {
+            Ok(project) => (project.entry.clone(), project.env()),
+            Err(e) => {
+                eprintln!("Manifest error: {e}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        (path.to_path_buf(), Env::for_file(path))
+    };
+    env.allow_file_access();
+
+    let src = std::fs::read_to_string(&file)?;
+    let parsed = match Parsed::parse(&src) {
+        Ok((s, _)) if !s.trim().is_empty() => {
+            eprintln!("Input not fully consumed by parser: {s}");
+            std::process::exit(1);
+        }
+        Ok((_, parsed)) => parsed,
+        Err(e) => {
+            eprintln!("Parse error: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match parsed.eval(&mut env) {
+        Ok(v) => println!("{:?}", v),
+        Err(e) => {
+            eprintln!("Evaluation error: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
 fn greeting(stdout: &mut Stdout) -> io::Result<()> {
-    write!(
+    writeln!(
         stdout,
-        "Spool CLI {} on {} (parser version: {})\n",
+        "Spool CLI {} on {} (parser version: {})",
         VERSION,
         std::env::consts::OS,
         spool::VERSION
     )?;
-    write!(stdout, "Type '/help' for a list of commands\n")?;
+    writeln!(stdout, "Type '/help' for a list of commands")?;
 
     Ok(())
 }
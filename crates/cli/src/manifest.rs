@@ -0,0 +1,267 @@
+use serde::Deserialize;
+use spool::Env;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
+
+pub const MANIFEST_FILE: &str = "spool.toml";
+const DEFAULT_ENTRY: &str = "main.spool";
+
+/// The contents of a `spool.toml`.
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct Manifest {
+    package: PackageMeta,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct PackageMeta {
+    name: String,
+    /// The entry file, relative to the manifest.
+    #[serde(default = "default_entry")]
+    entry: PathBuf,
+}
+
+fn default_entry() -> PathBuf {
+    DEFAULT_ENTRY.into()
+}
+
+/// A dependency on the package in another directory, relative to the manifest.
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct Dependency {
+    path: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+    /// Two dependencies of the same name that refer to different packages.
+    Conflict {
+        name: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "failed to read {}: {error}", path.display()),
+            Self::Parse { path, error } => {
+                write!(f, "failed to parse {}: {error}", path.display())
+            }
+            Self::Conflict {
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "dependency `{name}` refers to both {} and {}",
+                first.display(),
+                second.display()
+            ),
+        }
+    }
+}
+
+impl Manifest {
+    /// Reads the manifest in `dir`.
+    pub fn read(dir: &Path) -> Result<Self, ManifestError> {
+        let path = dir.join(MANIFEST_FILE);
+        let src = std::fs::read_to_string(&path).map_err(|error| ManifestError::Io {
+            path: path.clone(),
+            error,
+        })?;
+
+        toml::from_str(&src).map_err(|error| ManifestError::Parse { path, error })
+    }
+}
+
+/// A package whose dependencies were resolved.
+#[derive(Debug, PartialEq)]
+pub struct Project {
+    pub name: String,
+    pub entry: PathBuf,
+    /// The entry files of all dependencies, including indirect ones, by name. This is the search
+    /// path imports fall back to.
+    pub search_path: BTreeMap<String, PathBuf>,
+}
+
+impl Project {
+    /// Reads the manifest in `dir`, and the manifests of its dependencies, recursively.
+    pub fn resolve(dir: &Path) -> Result<Self, ManifestError> {
+        let dir = canonicalize(dir)?;
+        let manifest = Manifest::read(&dir)?;
+
+        let mut search_path: BTreeMap<String, PathBuf> = BTreeMap::new();
+        let mut queue = VecDeque::from([(dir.clone(), manifest.dependencies)]);
+
+        while let Some((parent, deps)) = queue.pop_front() {
+            for (name, dep) in deps {
+                let dep_dir = canonicalize(&parent.join(dep.path))?;
+                let dep_manifest = Manifest::read(&dep_dir)?;
+                let entry = dep_dir.join(dep_manifest.package.entry);
+
+                match search_path.get(&name) {
+                    Some(first) if *first == entry => continue,
+                    Some(first) => {
+                        return Err(ManifestError::Conflict {
+                            name,
+                            first: first.clone(),
+                            second: entry,
+                        });
+                    }
+                    None => {}
+                }
+
+                search_path.insert(name, entry);
+                queue.push_back((dep_dir, dep_manifest.dependencies));
+            }
+        }
+
+        Ok(Self {
+            name: manifest.package.name,
+            entry: dir.join(manifest.package.entry),
+            search_path,
+        })
+    }
+
+    /// Creates the top-level scope of the entry file, where every dependency can be imported.
+    pub fn env(&self) -> Env {
+        let mut env = Env::for_file(&self.entry);
+        self.add_packages(&mut env);
+        env
+    }
+
+    pub fn add_packages(&self, env: &mut Env) {
+        for (name, entry) in &self.search_path {
+            env.add_package(name, entry);
+        }
+    }
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, ManifestError> {
+    path.canonicalize().map_err(|error| ManifestError::Io {
+        path: path.into(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use spool::Parsed;
+    use tempfile::TempDir;
+
+    use super::{ManifestError, Project};
+
+    /// Writes `files` to a fresh directory, and returns its canonical path along with the handle
+    /// that removes it once dropped.
+    fn write_files(files: &[(&str, &str)]) -> (TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+
+        for (name, src) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, src).unwrap();
+        }
+
+        let path = dir.path().canonicalize().unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn resolve_path_dependencies() {
+        let (_files, dir) = write_files(&[
+            (
+                "app/spool.toml",
+                "[package]\nname = \"app\"\nentry = \"src/main.spool\"\n\n[dependencies]\ngeom = { path = \"../geom\" }",
+            ),
+            ("app/src/main.spool", "import geom; geom.area(2)"),
+            (
+                "geom/spool.toml",
+                "[package]\nname = \"geom\"\n\n[dependencies]\nnum = { path = \"../num\" }",
+            ),
+            (
+                "geom/main.spool",
+                "import num; pub func area(r) => num.square(r) * 3",
+            ),
+            ("num/spool.toml", "[package]\nname = \"num\""),
+            ("num/main.spool", "pub func square(n) => n * n"),
+        ]);
+
+        let project = Project::resolve(&dir.join("app")).unwrap();
+        assert_eq!(project.name, "app");
+        assert_eq!(project.entry, dir.join("app/src/main.spool"));
+        assert_eq!(
+            project.search_path,
+            BTreeMap::from([
+                ("geom".into(), dir.join("geom/main.spool")),
+                ("num".into(), dir.join("num/main.spool")),
+            ])
+        );
+
+        let src = std::fs::read_to_string(&project.entry).unwrap();
+        let (_, parsed) = Parsed::parse(&src).unwrap();
+        assert_eq!(
+            format!("{:?}", parsed.eval(&mut project.env())),
+            "Ok(Real(12.0))"
+        )
+    }
+
+    #[test]
+    fn missing_dependency() {
+        let (_files, dir) = write_files(&[(
+            "spool.toml",
+            "[package]\nname = \"app\"\n\n[dependencies]\ngone = { path = \"../gone\" }",
+        )]);
+
+        assert!(matches!(
+            Project::resolve(&dir),
+            Err(ManifestError::Io { path, .. }) if path == dir.join("../gone")
+        ))
+    }
+
+    #[test]
+    fn conflicting_dependencies() {
+        let (_files, dir) = write_files(&[
+            (
+                "app/spool.toml",
+                "[package]\nname = \"app\"\n\n[dependencies]\nutil = { path = \"../a\" }\nb = { path = \"../b\" }",
+            ),
+            ("a/spool.toml", "[package]\nname = \"util\""),
+            (
+                "b/spool.toml",
+                "[package]\nname = \"b\"\n\n[dependencies]\nutil = { path = \"../c\" }",
+            ),
+            ("c/spool.toml", "[package]\nname = \"util\""),
+        ]);
+
+        assert!(matches!(
+            Project::resolve(&dir.join("app")),
+            Err(ManifestError::Conflict { name, .. }) if name == "util"
+        ))
+    }
+
+    #[test]
+    fn invalid_manifest() {
+        let (_files, dir) = write_files(&[("spool.toml", "[package]\nentry = 1")]);
+
+        assert!(matches!(
+            Project::resolve(&dir),
+            Err(ManifestError::Parse { .. })
+        ))
+    }
+}
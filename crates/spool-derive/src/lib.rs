@@ -0,0 +1,398 @@
+//! Derive macros for `spool::IntoVal` and `spool::FromVal`, and the `spool::export` attribute,
+//! all re-exported by `spool`.
+//!
+//! Structs with named fields map to Spool struct instances of the same name, field by field.
+//! Enums map unit variants to their name as a string, and variants with named fields to struct
+//! instances named after the variant. Since Spool names can't contain `_`, fields are named in
+//! `camelCase` by default, so `max_speed` is `maxSpeed` in scripts. Names can be changed with
+//! `#[spool(rename = "...")]` on the type, a field or a variant, and
+//! `#[spool(rename_all = "...")]` on the type or a variant, which renames its fields (or an
+//! enum's variants) to `camelCase`, `PascalCase`, `lowercase` or `UPPERCASE` instead.
+//!
+//! `#[spool::export]` turns a function whose parameters implement `FromVal` into a native
+//! function that scripts call by its name in `camelCase`, or by `#[spool::export(name = "...")]`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Error, Fields, FnArg, Generics, Ident, ItemFn, LitStr, Pat,
+    meta::parser, parse_macro_input, parse_quote,
+};
+
+#[proc_macro_derive(IntoVal, attributes(spool))]
+pub fn derive_into_val(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_val(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(FromVal, attributes(spool))]
+pub fn derive_from_val(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_val(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_attribute]
+pub fn export(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut name = None;
+    let attr_parser = parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            Err(meta.error("expected `name`"))
+        }
+    });
+    parse_macro_input!(attr with attr_parser);
+
+    let item = parse_macro_input!(item as ItemFn);
+    export_fn(name, &item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The options given in `#[spool(...)]` attributes.
+#[derive(Default)]
+struct Attrs {
+    rename: Option<String>,
+    rename_all: Option<Case>,
+}
+
+impl Attrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = Self::default();
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("spool")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    out.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else if meta.path.is_ident("rename_all") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.rename_all = Some(Case::parse(&lit)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename` or `rename_all`"))
+                }
+            })?;
+        }
+
+        Ok(out)
+    }
+
+    /// The name `ident` has in Spool.
+    fn name(&self, ident: &Ident, case: Option<Case>) -> String {
+        match (&self.rename, case) {
+            (Some(name), _) => name.clone(),
+            (None, Some(case)) => case.apply(&ident.to_string()),
+            (None, None) => ident.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Case {
+    Camel,
+    Pascal,
+    Lower,
+    Upper,
+}
+
+impl Case {
+    fn parse(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "camelCase" => Self::Camel,
+            "PascalCase" => Self::Pascal,
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            _ => {
+                return Err(Error::new(
+                    lit.span(),
+                    "expected `camelCase`, `PascalCase`, `lowercase` or `UPPERCASE`",
+                ));
+            }
+        })
+    }
+
+    /// Converts a `snake_case` or `PascalCase` name.
+    fn apply(self, name: &str) -> String {
+        let mut words: Vec<String> = vec![];
+        for part in name.split('_').filter(|p| !p.is_empty()) {
+            for c in part.chars() {
+                match words.last_mut() {
+                    Some(word) if !c.is_uppercase() || word.is_empty() => word.push(c),
+                    _ => words.push(c.into()),
+                }
+            }
+            words.push(String::new());
+        }
+        let words = words.iter().filter(|w| !w.is_empty());
+
+        let capitalize = |w: &String| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars.flat_map(char::to_lowercase)))
+                .into_iter()
+                .flatten()
+                .collect::<String>()
+        };
+
+        match self {
+            Self::Camel => words
+                .enumerate()
+                .map(|(i, w)| match i {
+                    0 => w.to_lowercase(),
+                    _ => capitalize(w),
+                })
+                .collect(),
+            Self::Pascal => words.map(capitalize).collect(),
+            Self::Lower => words.map(|w| w.to_lowercase()).collect(),
+            Self::Upper => words.map(|w| w.to_uppercase()).collect(),
+        }
+    }
+}
+
+/// A named field, and its name in Spool.
+struct Field {
+    ident: Ident,
+    name: String,
+}
+
+/// Returns the named fields of a struct or variant, renamed to `case`, or to `camelCase` if it's
+/// `None`. Tuple fields have no name to map to.
+fn named_fields(fields: &Fields, case: Option<Case>) -> syn::Result<Option<Vec<Field>>> {
+    let case = case.unwrap_or(Case::Camel);
+
+    match fields {
+        Fields::Named(named) => named
+            .named
+            .iter()
+            .map(|f| {
+                // named fields always have an identifier
+                let ident = f.ident.clone().unwrap();
+                let name = Attrs::parse(&f.attrs)?.name(&ident, Some(case));
+                Ok(Field { ident, name })
+            })
+            .collect::<syn::Result<_>>()
+            .map(Some),
+        Fields::Unit => Ok(None),
+        Fields::Unnamed(_) => Err(Error::new_spanned(
+            fields,
+            "tuple fields are not supported, since Spool fields need a name",
+        )),
+    }
+}
+
+/// Adds `bound` to every type parameter.
+fn add_bounds(generics: &Generics, bound: TokenStream) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+/// Keeps `item` as it is, and adds a struct of the same name that implements
+/// `spool::NativeExport`. The struct has braces so that it doesn't clash with the function.
+fn export_fn(name: Option<String>, item: &ItemFn) -> syn::Result<TokenStream> {
+    let sig = &item.sig;
+    if !sig.generics.params.is_empty() || sig.asyncness.is_some() {
+        return Err(Error::new_spanned(
+            sig,
+            "exported functions can't be generic or async",
+        ));
+    }
+
+    let ident = &sig.ident;
+    let vis = &item.vis;
+    let name = name.unwrap_or_else(|| Case::Camel.apply(&ident.to_string()));
+
+    let params = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Typed(arg) => match &*arg.pat {
+                Pat::Ident(pat) => Ok((pat.ident.to_string(), &arg.ty)),
+                pat => Err(Error::new_spanned(pat, "expected a parameter name")),
+            },
+            FnArg::Receiver(arg) => Err(Error::new_spanned(
+                arg,
+                "exported functions can't take `self`",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let arity = params.len();
+    let args = params
+        .iter()
+        .enumerate()
+        .map(|(i, (param, ty))| quote!(::spool::export_arg::<#ty>(#name, #param, &args[#i])?));
+
+    Ok(quote! {
+        #item
+
+        #[allow(non_camel_case_types)]
+        #vis struct #ident {}
+
+        impl ::spool::NativeExport for #ident {
+            const NAME: &'static str = #name;
+            const ARITY: usize = #arity;
+
+            fn call(
+                args: &[::spool::Val],
+            ) -> ::std::result::Result<::spool::Val, ::spool::EvalError> {
+                ::spool::IntoNativeResult::into_native_result(#ident(#(#args),*))
+            }
+        }
+    })
+}
+
+fn into_val(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let attrs = Attrs::parse(&input.attrs)?;
+    let ident = &input.ident;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let name = attrs.name(ident, None);
+            let fields = named_fields(&data.fields, attrs.rename_all)?.unwrap_or_default();
+            let (idents, names): (Vec<_>, Vec<_>) =
+                fields.iter().map(|f| (&f.ident, &f.name)).unzip();
+
+            quote! {
+                ::spool::Val::new_struct(#name, [
+                    #((#names.to_string(), ::spool::IntoVal::into_val(self.#idents)),)*
+                ])
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|v| {
+                    let v_attrs = Attrs::parse(&v.attrs)?;
+                    let v_ident = &v.ident;
+                    let name = v_attrs.name(v_ident, attrs.rename_all);
+
+                    Ok(match named_fields(&v.fields, v_attrs.rename_all)? {
+                        None => quote! {
+                            Self::#v_ident => ::spool::Val::Str(#name.to_string()),
+                        },
+                        Some(fields) => {
+                            let (idents, names): (Vec<_>, Vec<_>) =
+                                fields.iter().map(|f| (&f.ident, &f.name)).unzip();
+
+                            quote! {
+                                Self::#v_ident { #(#idents),* } => ::spool::Val::new_struct(#name, [
+                                    #((#names.to_string(), ::spool::IntoVal::into_val(#idents)),)*
+                                ]),
+                            }
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => return Err(Error::new_spanned(input, "unions are not supported")),
+    };
+
+    let generics = add_bounds(&input.generics, quote!(::spool::IntoVal));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::spool::IntoVal for #ident #ty_generics #where_clause {
+            fn into_val(self) -> ::spool::Val {
+                #body
+            }
+        }
+    })
+}
+
+fn from_val(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let attrs = Attrs::parse(&input.attrs)?;
+    let ident = &input.ident;
+    let val = format_ident!("val");
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let name = attrs.name(ident, None);
+            let construct = match named_fields(&data.fields, attrs.rename_all)? {
+                None => quote!(Self),
+                Some(fields) => {
+                    let (idents, names): (Vec<_>, Vec<_>) =
+                        fields.iter().map(|f| (&f.ident, &f.name)).unzip();
+                    quote!(Self { #(#idents: fields.take(#names)?),* })
+                }
+            };
+
+            quote! {
+                #[allow(unused_mut, unused_variables)]
+                let mut fields = ::spool::Fields::from_val(#name, #val)?;
+                Ok(#construct)
+            }
+        }
+        Data::Enum(data) => {
+            let name = attrs.name(ident, None);
+            let arms = data
+                .variants
+                .iter()
+                .map(|v| {
+                    let v_attrs = Attrs::parse(&v.attrs)?;
+                    let v_ident = &v.ident;
+                    let v_name = v_attrs.name(v_ident, attrs.rename_all);
+
+                    Ok(match named_fields(&v.fields, v_attrs.rename_all)? {
+                        None => quote! {
+                            (#v_name, None) => Ok(Self::#v_ident),
+                        },
+                        Some(fields) => {
+                            let (idents, names): (Vec<_>, Vec<_>) =
+                                fields.iter().map(|f| (&f.ident, &f.name)).unzip();
+
+                            quote! {
+                                #[allow(unused_mut)]
+                                (#v_name, Some(mut fields)) => Ok(Self::#v_ident {
+                                    #(#idents: fields.take(#names)?),*
+                                }),
+                            }
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! {
+                let (variant, fields) = ::spool::Fields::variant(#name, #val)?;
+                match (variant.as_str(), fields) {
+                    #(#arms)*
+                    _ => Err(::spool::EvalError::InvalidType {
+                        expected: ::std::format!("a variant of {}", #name),
+                        received: variant.clone(),
+                    }),
+                }
+            }
+        }
+        Data::Union(_) => return Err(Error::new_spanned(input, "unions are not supported")),
+    };
+
+    let generics = add_bounds(&input.generics, quote!(::spool::FromVal));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::spool::FromVal for #ident #ty_generics #where_clause {
+            fn from_val(#val: ::spool::Val) -> ::std::result::Result<Self, ::spool::EvalError> {
+                #body
+            }
+        }
+    })
+}
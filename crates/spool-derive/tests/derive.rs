@@ -0,0 +1,146 @@
+use spool::{EvalError, FromVal, Interpreter, IntoVal, Val};
+
+#[derive(IntoVal, FromVal, Debug, PartialEq, Clone)]
+#[spool(rename_all = "camelCase")]
+struct Config {
+    max_speed: f32,
+    #[spool(rename = "label")]
+    name: String,
+    tags: Vec<String>,
+    parent: Option<Box<Config>>,
+}
+
+#[derive(IntoVal, FromVal, Debug, PartialEq)]
+enum Shape {
+    Empty,
+    #[spool(rename = "Circle")]
+    Round {
+        radius: f32,
+    },
+}
+
+#[test]
+fn derive_struct() {
+    let config = Config {
+        max_speed: 2.,
+        name: "main".into(),
+        tags: vec!["a".into()],
+        parent: None,
+    };
+    assert_eq!(Config::from_val(config.clone().into_val()), Ok(config));
+
+    let mut interp = Interpreter::new();
+    interp
+        .eval_str("struct Config { maxSpeed, label, tags = [] }")
+        .unwrap();
+
+    assert_eq!(
+        Config::from_val(interp.eval_str("Config(3, \"x\")").unwrap()),
+        Ok(Config {
+            max_speed: 3.,
+            name: "x".into(),
+            tags: vec![],
+            parent: None
+        })
+    );
+    assert_eq!(
+        Config::from_val(interp.eval_str("[\"maxSpeed\": 3, \"tags\": []]").unwrap()),
+        Err(EvalError::MissingField {
+            ty: "Config".into(),
+            field: "label".into()
+        })
+    );
+    assert_eq!(
+        Config::from_val(interp.eval_str("Config(\"fast\", \"x\")").unwrap()),
+        Err(EvalError::InvalidField {
+            ty: "Config".into(),
+            field: "maxSpeed".into(),
+            error: Box::new(EvalError::InvalidType {
+                expected: "a real number".into(),
+                received: "String".into()
+            })
+        })
+    )
+}
+
+#[test]
+fn derive_struct_into_script() {
+    let mut interp = Interpreter::new();
+    interp.set_global(
+        "config",
+        Config {
+            max_speed: 2.,
+            name: "main".into(),
+            tags: vec![],
+            parent: None,
+        }
+        .into_val(),
+    );
+
+    assert_eq!(
+        interp.eval_str("[config.maxSpeed, config.label, config.parent]"),
+        Ok(Val::List(vec![
+            Val::Real(2.),
+            Val::Str("main".into()),
+            Val::Nil
+        ]))
+    )
+}
+
+#[derive(IntoVal, FromVal, Debug, PartialEq)]
+struct Retry {
+    max_retries: u32,
+    backoff_ms: Option<u32>,
+}
+
+#[test]
+fn fields_are_camel_case_by_default() {
+    let mut interp = Interpreter::new();
+    interp
+        .eval_str("struct Retry { maxRetries, backoffMs = nil }")
+        .unwrap();
+
+    assert_eq!(
+        Retry::from_val(interp.eval_str("Retry(3)").unwrap()),
+        Ok(Retry {
+            max_retries: 3,
+            backoff_ms: None
+        })
+    );
+
+    interp.set_global(
+        "retry",
+        Retry {
+            max_retries: 5,
+            backoff_ms: Some(100),
+        }
+        .into_val(),
+    );
+    assert_eq!(
+        interp.eval_str("retry.maxRetries + retry.backoffMs"),
+        Ok(Val::Real(105.))
+    )
+}
+
+#[test]
+fn derive_enum() {
+    assert_eq!(Shape::Empty.into_val(), Val::Str("Empty".into()));
+    assert_eq!(
+        Shape::from_val(Shape::Round { radius: 1. }.into_val()),
+        Ok(Shape::Round { radius: 1. })
+    );
+
+    let mut interp = Interpreter::new();
+    interp.eval_str("struct Circle { radius }").unwrap();
+    assert_eq!(
+        Shape::from_val(interp.eval_str("Circle(2)").unwrap()),
+        Ok(Shape::Round { radius: 2. })
+    );
+    assert_eq!(
+        Shape::from_val(Val::Str("Square".into())),
+        Err(EvalError::InvalidType {
+            expected: "a variant of Shape".into(),
+            received: "Square".into()
+        })
+    )
+}
@@ -0,0 +1,67 @@
+use spool::{ErrorVal, EvalError, Interpreter, Val};
+
+#[spool::export]
+fn scale_by(x: f64, factor: Option<f64>) -> f64 {
+    x * factor.unwrap_or(2.)
+}
+
+#[spool::export(name = "greet")]
+fn hello(name: String) -> Result<String, ErrorVal> {
+    match name.is_empty() {
+        true => Err(ErrorVal {
+            kind: "ValueError".into(),
+            message: "name is empty".into(),
+        }),
+        false => Ok(format!("hello {name}")),
+    }
+}
+
+fn eval_with_exports(src: &str) -> Result<Val, EvalError> {
+    let mut interp = Interpreter::new();
+    interp.register_export::<scale_by>();
+    interp.register_export::<hello>();
+
+    interp.eval_str(src)
+}
+
+#[test]
+fn call_export() {
+    assert_eq!(
+        eval_with_exports("[scaleBy(2, 3), scaleBy(2, nil)]"),
+        Ok(Val::List(vec![Val::Real(6.), Val::Real(4.)]))
+    );
+    assert_eq!(
+        eval_with_exports("greet(\"Ann\")"),
+        Ok(Val::Str("hello Ann".into()))
+    );
+    assert_eq!(scale_by(1., None), 2.)
+}
+
+#[test]
+fn export_type_mismatch() {
+    assert_eq!(
+        eval_with_exports("scaleBy(2, \"x\")"),
+        Err(EvalError::InvalidType {
+            expected: "a real number for argument `factor` of scaleBy".into(),
+            received: "String".into()
+        })
+    );
+    assert_eq!(
+        eval_with_exports("scaleBy(2)"),
+        Err(EvalError::TooFewArguments {
+            expected: 2,
+            received: 1
+        })
+    )
+}
+
+#[test]
+fn export_errors_are_catchable() {
+    assert_eq!(
+        eval_with_exports("try { greet(\"\") } catch e { [e.kind, e.message] }"),
+        Ok(Val::List(vec![
+            Val::Str("ValueError".into()),
+            Val::Str("name is empty".into())
+        ]))
+    )
+}
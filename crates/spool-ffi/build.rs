@@ -0,0 +1,27 @@
+/// Set to copy the generated header over `include/spool.h`, the one that's checked in.
+const UPDATE_HEADER_VAR: &str = "SPOOL_FFI_UPDATE_HEADER";
+
+fn main() {
+    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{dir}/cbindgen.toml")).unwrap();
+
+    // the header is generated into `OUT_DIR`, since builds must not write to the source tree.
+    // `tests/header.rs` fails if `include/spool.h` has fallen behind it
+    let header = format!("{out_dir}/spool.h");
+    cbindgen::Builder::new()
+        .with_crate(&dir)
+        .with_config(config)
+        .generate()
+        .expect("failed to generate the C header")
+        .write_to_file(&header);
+
+    if std::env::var_os(UPDATE_HEADER_VAR).is_some() {
+        std::fs::copy(&header, format!("{dir}/include/spool.h"))
+            .expect("failed to update include/spool.h");
+    }
+
+    println!("cargo::rerun-if-changed=src");
+    println!("cargo::rerun-if-changed=cbindgen.toml");
+    println!("cargo::rerun-if-env-changed={UPDATE_HEADER_VAR}");
+}
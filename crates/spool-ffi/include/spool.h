@@ -0,0 +1,173 @@
+#ifndef SPOOL_H
+#define SPOOL_H
+
+/* Generated by cbindgen from spool-ffi. Don't edit this file by hand, regenerate it with `SPOOL_FFI_UPDATE_HEADER=1 cargo build -p spool-ffi`. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum SpoolValueType {
+  SPOOL_VALUE_TYPE_NIL,
+  SPOOL_VALUE_TYPE_UNIT,
+  SPOOL_VALUE_TYPE_NUMBER,
+  SPOOL_VALUE_TYPE_STRING,
+  SPOOL_VALUE_TYPE_BOOL,
+  SPOOL_VALUE_TYPE_LIST,
+  SPOOL_VALUE_TYPE_MAP,
+  /**
+   * Any other value, such as a function or struct instance.
+   */
+  SPOOL_VALUE_TYPE_OTHER,
+} SpoolValueType;
+
+/**
+ * An interpreter, which keeps its globals across calls to [`spool_eval`].
+ */
+typedef struct SpoolInterpreter SpoolInterpreter;
+
+/**
+ * A value produced by a script, or passed to one.
+ */
+typedef struct SpoolValue SpoolValue;
+
+/**
+ * A native function. `args` holds `argc` values, which are only valid during the call. It
+ * returns a new value, or `NULL` to fail the call.
+ */
+typedef struct SpoolValue *(*SpoolNativeFn)(const struct SpoolValue *const *args,
+                                            size_t argc,
+                                            void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates an interpreter, or returns `NULL` if that failed.
+ */
+struct SpoolInterpreter *spool_interpreter_new(void);
+
+/**
+ * Destroys an interpreter.
+ *
+ * # Safety
+ *
+ * `interp` must be `NULL` or have been returned by [`spool_interpreter_new`], and must not be
+ * used afterwards.
+ */
+void spool_interpreter_free(struct SpoolInterpreter *interp);
+
+/**
+ * Evaluates `src`, returning the value of its last statement, or `NULL` if it failed, in which
+ * case [`spool_last_error`] describes why.
+ *
+ * # Safety
+ *
+ * `interp` must be a live interpreter, and `src` a NUL terminated string.
+ */
+struct SpoolValue *spool_eval(struct SpoolInterpreter *interp, const char *src);
+
+/**
+ * Returns the message of the last error, or `NULL` if the last evaluation succeeded. The
+ * message is owned by the interpreter, and valid until the next call to [`spool_eval`].
+ *
+ * # Safety
+ *
+ * `interp` must be a live interpreter.
+ */
+const char *spool_last_error(const struct SpoolInterpreter *interp);
+
+/**
+ * Makes `callback` callable from scripts as `name`, with exactly `arity` arguments. Returns
+ * `false` without registering it if `callback` is `NULL` or `name` isn't a valid identifier,
+ * such as a keyword or a name containing `_`. If registering it panicked,
+ * [`spool_last_error`] describes why.
+ *
+ * # Safety
+ *
+ * `interp` must be a live interpreter, and `name` a NUL terminated string. `callback` must be
+ * safe to call with `user_data` for as long as the interpreter lives, from any thread the
+ * interpreter is used on.
+ */
+bool spool_register_native(struct SpoolInterpreter *interp,
+                           const char *name,
+                           size_t arity,
+                           SpoolNativeFn callback,
+                           void *user_data);
+
+/**
+ * Returns the type of `value`.
+ *
+ * # Safety
+ *
+ * `value` must be a live value.
+ */
+enum SpoolValueType spool_value_type(const struct SpoolValue *value);
+
+/**
+ * Writes the number `value` holds to `out`. Returns `false`, leaving `out` as it is, if
+ * `value` isn't a number.
+ *
+ * # Safety
+ *
+ * `value` must be a live value, and `out` valid for writes.
+ */
+bool spool_value_as_number(const struct SpoolValue *value, double *out);
+
+/**
+ * Formats `value`: strings without quotes, numbers and booleans as their digits and `true` or
+ * `false`, `nil` and `()` as written in scripts, and any other value in its debug form, such as
+ * `List([Real(1.0)])`. The string has to be freed with [`spool_string_free`]. Returns `NULL` if
+ * the string contains a NUL byte, or if formatting failed.
+ *
+ * # Safety
+ *
+ * `value` must be a live value.
+ */
+char *spool_value_to_string(const struct SpoolValue *value);
+
+/**
+ * Frees a string returned by [`spool_value_to_string`].
+ *
+ * # Safety
+ *
+ * `s` must be `NULL` or have been returned by [`spool_value_to_string`].
+ */
+void spool_string_free(char *s);
+
+/**
+ * Creates a number.
+ */
+struct SpoolValue *spool_value_new_number(double n);
+
+/**
+ * Creates a string, or returns `NULL` if `s` isn't UTF-8.
+ *
+ * # Safety
+ *
+ * `s` must be a NUL terminated string.
+ */
+struct SpoolValue *spool_value_new_string(const char *s);
+
+/**
+ * Creates `nil`.
+ */
+struct SpoolValue *spool_value_new_nil(void);
+
+/**
+ * Frees a value.
+ *
+ * # Safety
+ *
+ * `value` must be `NULL` or a value owned by the caller, and must not be used afterwards.
+ */
+void spool_value_free(struct SpoolValue *value);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SPOOL_H */
@@ -0,0 +1,451 @@
+//! A C ABI for embedding Spool, declared in `include/spool.h`, which is generated from this crate.
+//!
+//! Every object returned by a function called `*_new`, `spool_eval` or
+//! `spool_value_to_string` is owned by the caller, and has to be freed by the matching `*_free`
+//! function. Strings passed in have to be valid, NUL terminated UTF-8.
+//!
+//! Panics never unwind into the caller. Functions that take an interpreter report them through
+//! [`spool_last_error`], and the rest return `NULL` or `false`.
+
+use std::{
+    ffi::{CStr, CString, c_char, c_void},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use spool::{ErrorVal, EvalError, Identifier, Interpreter, Parse, Val};
+
+/// An interpreter, which keeps its globals across calls to [`spool_eval`].
+pub struct SpoolInterpreter {
+    interp: Interpreter,
+    last_error: Option<CString>,
+}
+
+impl SpoolInterpreter {
+    fn set_error(&mut self, message: impl Into<String>) {
+        // interior NUL bytes would cut the message short, so they're dropped
+        let message: Vec<u8> = message.into().into_bytes();
+        let message = message.into_iter().filter(|b| *b != 0).collect::<Vec<_>>();
+        self.last_error = CString::new(message).ok();
+    }
+}
+
+/// A value produced by a script, or passed to one.
+pub struct SpoolValue(Val);
+
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum SpoolValueType {
+    Nil,
+    Unit,
+    Number,
+    String,
+    Bool,
+    List,
+    Map,
+    /// Any other value, such as a function or struct instance.
+    Other,
+}
+
+/// A native function. `args` holds `argc` values, which are only valid during the call. It
+/// returns a new value, or `NULL` to fail the call.
+pub type SpoolNativeFn = Option<
+    unsafe extern "C" fn(
+        args: *const *const SpoolValue,
+        argc: usize,
+        user_data: *mut c_void,
+    ) -> *mut SpoolValue,
+>;
+
+/// The `user_data` passed to a native function, which the host is responsible for.
+struct UserData(*mut c_void);
+
+impl UserData {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+// SAFETY: an interpreter may be moved to another thread, so `spool_register_native` requires
+// `user_data` to be usable from whichever thread the interpreter runs on.
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+/// Reads the string `s`, or returns `None` if it's `NULL` or not UTF-8.
+///
+/// # Safety
+///
+/// `s` must be `NULL` or point to a NUL terminated string.
+unsafe fn read_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+/// Runs `f`, catching a panic so that it doesn't unwind out of an `extern "C"` function, which
+/// would abort the host. A panic is returned as its message.
+fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = match payload.downcast_ref::<&str>() {
+            Some(s) => s,
+            None => payload.downcast_ref::<String>().map_or("", String::as_str),
+        };
+
+        format!("panicked: {message}")
+    })
+}
+
+fn into_raw(val: Val) -> *mut SpoolValue {
+    Box::into_raw(Box::new(SpoolValue(val)))
+}
+
+/// Creates an interpreter, or returns `NULL` if that failed.
+#[unsafe(no_mangle)]
+pub extern "C" fn spool_interpreter_new() -> *mut SpoolInterpreter {
+    catch_panic(|| {
+        Box::into_raw(Box::new(SpoolInterpreter {
+            interp: Interpreter::new(),
+            last_error: None,
+        }))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Destroys an interpreter.
+///
+/// # Safety
+///
+/// `interp` must be `NULL` or have been returned by [`spool_interpreter_new`], and must not be
+/// used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_interpreter_free(interp: *mut SpoolInterpreter) {
+    if !interp.is_null() {
+        let _ = catch_panic(|| drop(unsafe { Box::from_raw(interp) }));
+    }
+}
+
+/// Evaluates `src`, returning the value of its last statement, or `NULL` if it failed, in which
+/// case [`spool_last_error`] describes why.
+///
+/// # Safety
+///
+/// `interp` must be a live interpreter, and `src` a NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_eval(
+    interp: *mut SpoolInterpreter,
+    src: *const c_char,
+) -> *mut SpoolValue {
+    let Some(interp) = (unsafe { interp.as_mut() }) else {
+        return ptr::null_mut();
+    };
+
+    let Some(src) = (unsafe { read_str(src) }) else {
+        interp.set_error("the source is not valid UTF-8");
+        return ptr::null_mut();
+    };
+
+    match catch_panic(|| interp.interp.eval_str(src)) {
+        Ok(Ok(val)) => {
+            interp.last_error = None;
+            into_raw(val)
+        }
+        Ok(Err(e)) => {
+            interp.set_error(e.to_string());
+            ptr::null_mut()
+        }
+        Err(panic) => {
+            interp.set_error(panic);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the message of the last error, or `NULL` if the last evaluation succeeded. The
+/// message is owned by the interpreter, and valid until the next call to [`spool_eval`].
+///
+/// # Safety
+///
+/// `interp` must be a live interpreter.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_last_error(interp: *const SpoolInterpreter) -> *const c_char {
+    match unsafe { interp.as_ref() }.and_then(|i| i.last_error.as_ref()) {
+        Some(e) => e.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Makes `callback` callable from scripts as `name`, with exactly `arity` arguments. Returns
+/// `false` without registering it if `callback` is `NULL` or `name` isn't a valid identifier,
+/// such as a keyword or a name containing `_`. If registering it panicked,
+/// [`spool_last_error`] describes why.
+///
+/// # Safety
+///
+/// `interp` must be a live interpreter, and `name` a NUL terminated string. `callback` must be
+/// safe to call with `user_data` for as long as the interpreter lives, from any thread the
+/// interpreter is used on.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_register_native(
+    interp: *mut SpoolInterpreter,
+    name: *const c_char,
+    arity: usize,
+    callback: SpoolNativeFn,
+    user_data: *mut c_void,
+) -> bool {
+    let (Some(interp), Some(name), Some(callback)) = (
+        unsafe { interp.as_mut() },
+        unsafe { read_str(name) },
+        callback,
+    ) else {
+        return false;
+    };
+
+    // scripts couldn't call the function by any other name
+    if !matches!(Identifier::parse(name), Ok((rest, id)) if rest.is_empty() && id.0 == name) {
+        return false;
+    }
+
+    let user_data = UserData(user_data);
+    let fn_name = name.to_string();
+    let registered = catch_panic(|| {
+        interp.interp.register_native(name, arity, move |args| {
+            let args: Vec<SpoolValue> = args.iter().cloned().map(SpoolValue).collect();
+            let arg_ptrs: Vec<*const SpoolValue> = args.iter().map(|a| a as *const _).collect();
+
+            let out = unsafe { callback(arg_ptrs.as_ptr(), arg_ptrs.len(), user_data.get()) };
+            if out.is_null() {
+                return Err(EvalError::from(ErrorVal {
+                    kind: "NativeError".into(),
+                    message: format!("native function `{fn_name}` failed"),
+                }));
+            }
+
+            Ok(unsafe { Box::from_raw(out) }.0)
+        })
+    });
+
+    match registered {
+        Ok(()) => true,
+        Err(panic) => {
+            interp.set_error(panic);
+            false
+        }
+    }
+}
+
+/// Returns the type of `value`.
+///
+/// # Safety
+///
+/// `value` must be a live value.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_value_type(value: *const SpoolValue) -> SpoolValueType {
+    match unsafe { value.as_ref() }.map(|v| &v.0) {
+        None | Some(Val::Nil) => SpoolValueType::Nil,
+        Some(Val::Unit) => SpoolValueType::Unit,
+        Some(Val::Real(_)) => SpoolValueType::Number,
+        Some(Val::Str(_)) => SpoolValueType::String,
+        Some(Val::Bool(_)) => SpoolValueType::Bool,
+        Some(Val::List(_)) => SpoolValueType::List,
+        Some(Val::Map(_)) => SpoolValueType::Map,
+        Some(_) => SpoolValueType::Other,
+    }
+}
+
+/// Writes the number `value` holds to `out`. Returns `false`, leaving `out` as it is, if
+/// `value` isn't a number.
+///
+/// # Safety
+///
+/// `value` must be a live value, and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_value_as_number(value: *const SpoolValue, out: *mut f64) -> bool {
+    match (unsafe { value.as_ref() }, out.is_null()) {
+        (Some(SpoolValue(Val::Real(r))), false) => {
+            unsafe { *out = f64::from(*r) };
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Formats `value`: strings without quotes, numbers and booleans as their digits and `true` or
+/// `false`, `nil` and `()` as written in scripts, and any other value in its debug form, such as
+/// `List([Real(1.0)])`. The string has to be freed with [`spool_string_free`]. Returns `NULL` if
+/// the string contains a NUL byte, or if formatting failed.
+///
+/// # Safety
+///
+/// `value` must be a live value.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_value_to_string(value: *const SpoolValue) -> *mut c_char {
+    let Some(SpoolValue(val)) = (unsafe { value.as_ref() }) else {
+        return ptr::null_mut();
+    };
+
+    let s = catch_panic(|| match val {
+        Val::Str(s) => s.clone(),
+        Val::Real(r) => r.to_string(),
+        Val::Bool(b) => b.to_string(),
+        Val::Nil => "nil".into(),
+        Val::Unit => "()".into(),
+        v => format!("{v:?}"),
+    });
+
+    s.ok()
+        .and_then(|s| CString::new(s).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Frees a string returned by [`spool_value_to_string`].
+///
+/// # Safety
+///
+/// `s` must be `NULL` or have been returned by [`spool_value_to_string`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Creates a number.
+#[unsafe(no_mangle)]
+pub extern "C" fn spool_value_new_number(n: f64) -> *mut SpoolValue {
+    into_raw(Val::Real(n as f32))
+}
+
+/// Creates a string, or returns `NULL` if `s` isn't UTF-8.
+///
+/// # Safety
+///
+/// `s` must be a NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_value_new_string(s: *const c_char) -> *mut SpoolValue {
+    match unsafe { read_str(s) } {
+        Some(s) => into_raw(Val::Str(s.into())),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Creates `nil`.
+#[unsafe(no_mangle)]
+pub extern "C" fn spool_value_new_nil() -> *mut SpoolValue {
+    into_raw(Val::Nil)
+}
+
+/// Frees a value.
+///
+/// # Safety
+///
+/// `value` must be `NULL` or a value owned by the caller, and must not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn spool_value_free(value: *mut SpoolValue) {
+    if !value.is_null() {
+        let _ = catch_panic(|| drop(unsafe { Box::from_raw(value) }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{CStr, c_void},
+        ptr,
+    };
+
+    use super::*;
+
+    unsafe extern "C" fn add(
+        args: *const *const SpoolValue,
+        argc: usize,
+        calls: *mut c_void,
+    ) -> *mut SpoolValue {
+        unsafe { *(calls as *mut u32) += 1 };
+
+        let mut sum = 0.;
+        for i in 0..argc {
+            let mut n = 0.;
+            if !unsafe { spool_value_as_number(*args.add(i), &mut n) } {
+                return ptr::null_mut();
+            }
+            sum += n;
+        }
+
+        spool_value_new_number(sum)
+    }
+
+    #[test]
+    fn eval_and_register() {
+        let mut calls = 0u32;
+
+        unsafe {
+            let interp = spool_interpreter_new();
+            assert!(spool_register_native(
+                interp,
+                c"add".as_ptr(),
+                2,
+                Some(add),
+                &mut calls as *mut u32 as *mut c_void
+            ));
+
+            for name in [c"my_add", c"for", c" add"] {
+                assert!(!spool_register_native(
+                    interp,
+                    name.as_ptr(),
+                    2,
+                    Some(add),
+                    ptr::null_mut()
+                ));
+            }
+
+            let val = spool_eval(interp, c"add(40, 2)".as_ptr());
+            let mut n = 0.;
+            assert!(spool_value_as_number(val, &mut n));
+            assert_eq!(n, 42.);
+            spool_value_free(val);
+
+            assert!(spool_eval(interp, c"add(1, \"x\")".as_ptr()).is_null());
+            assert_eq!(
+                CStr::from_ptr(spool_last_error(interp)).to_str(),
+                Ok(
+                    "uncaught error: Error(ErrorVal { kind: \"NativeError\", message: \"native function `add` failed\" })"
+                )
+            );
+            assert_eq!(calls, 2);
+
+            spool_interpreter_free(interp);
+        }
+    }
+
+    #[test]
+    fn report_panic() {
+        unsafe {
+            let interp = spool_interpreter_new();
+            (*interp)
+                .interp
+                .register_native("explode", 0, |_| panic!("out of fuses"));
+
+            assert!(spool_eval(interp, c"explode()".as_ptr()).is_null());
+            assert_eq!(
+                CStr::from_ptr(spool_last_error(interp)).to_str(),
+                Ok("panicked: out of fuses")
+            );
+
+            // the interpreter is still usable afterwards, even after panicking in a block
+            let unit = spool_eval(interp, c"bind x = 1".as_ptr());
+            assert!(!unit.is_null());
+            spool_value_free(unit);
+            assert!(spool_eval(interp, c"{ explode() }".as_ptr()).is_null());
+
+            let val = spool_eval(interp, c"x + 1".as_ptr());
+            let mut n = 0.;
+            assert!(spool_value_as_number(val, &mut n));
+            assert_eq!(n, 2.);
+            spool_value_free(val);
+
+            spool_interpreter_free(interp);
+        }
+    }
+}
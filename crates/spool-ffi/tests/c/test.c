@@ -0,0 +1,63 @@
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "spool.h"
+
+static SpoolValue *scale(const SpoolValue *const *args, size_t argc, void *user_data) {
+  double factor = *(double *)user_data;
+  double n;
+
+  assert(argc == 1);
+  if (!spool_value_as_number(args[0], &n)) {
+    return NULL;
+  }
+
+  return spool_value_new_number(n * factor);
+}
+
+static void expect_number(SpoolInterpreter *interp, const char *src, double expected) {
+  SpoolValue *value = spool_eval(interp, src);
+  double n;
+
+  assert(value != NULL);
+  assert(spool_value_type(value) == SPOOL_VALUE_TYPE_NUMBER);
+  assert(spool_value_as_number(value, &n));
+  assert(n == expected);
+  spool_value_free(value);
+}
+
+static void expect_string(SpoolInterpreter *interp, const char *src, const char *expected) {
+  SpoolValue *value = spool_eval(interp, src);
+  char *s;
+
+  assert(value != NULL);
+  s = spool_value_to_string(value);
+  assert(strcmp(s, expected) == 0);
+  spool_string_free(s);
+  spool_value_free(value);
+}
+
+int main(void) {
+  SpoolInterpreter *interp = spool_interpreter_new();
+  double factor = 2.5;
+
+  expect_number(interp, "bind x = 6; x * 7", 42);
+  expect_number(interp, "x + 1", 7);
+  expect_string(interp, "\"hello\"", "hello");
+  expect_string(interp, "true", "true");
+
+  assert(spool_eval(interp, "y") == NULL);
+  assert(strstr(spool_last_error(interp), "y") != NULL);
+
+  assert(spool_register_native(interp, "scale", 1, scale, &factor));
+  expect_number(interp, "scale(4)", 10);
+  assert(spool_last_error(interp) == NULL);
+
+  assert(spool_eval(interp, "scale(\"x\")") == NULL);
+  assert(strstr(spool_last_error(interp), "native function `scale` failed") != NULL);
+
+  spool_interpreter_free(interp);
+  puts("ok");
+  return 0;
+}
@@ -0,0 +1,39 @@
+use std::{env, path::PathBuf, process::Command};
+
+/// Builds `tests/c/test.c` against the header and shared library, and runs it.
+#[test]
+fn c_program() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // the test binary lives in `target/<profile>/deps`, next to the shared library
+    let lib_dir = env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(|deps| deps.parent())
+        .unwrap()
+        .to_path_buf();
+    let out = lib_dir.join("spool_ffi_c_test");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".into()))
+        .arg(dir.join("tests/c/test.c"))
+        .arg("-I")
+        .arg(dir.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .args(["-lspool_ffi", "-o"])
+        .arg(&out)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success());
+
+    let output = Command::new(&out)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
@@ -0,0 +1,12 @@
+/// Checks that `include/spool.h` matches the header generated from the current source. Run
+/// `SPOOL_FFI_UPDATE_HEADER=1 cargo build -p spool-ffi` to update it.
+#[test]
+fn header_is_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/spool.h"));
+    let checked_in = include_str!("../include/spool.h");
+
+    assert!(
+        generated == checked_in,
+        "include/spool.h is stale, run `SPOOL_FFI_UPDATE_HEADER=1 cargo build -p spool-ffi`"
+    );
+}
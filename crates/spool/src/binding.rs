@@ -1,14 +1,14 @@
 use crate::{
-    Eval, KEYWORDS, Parse, ParseError,
+    Eval, KEYWORDS, Parse, ParseError, builtins,
     expr::Expr,
     utils::{extract_ident, extract_whitespace, tag},
 };
 
-const BIND_TOKEN: &str = "bind";
+pub(crate) const BIND_TOKEN: &str = "bind";
 pub const ASSIGN_TOKEN: &str = "=";
 const IMMUTABLE_TOKEN: &str = "final";
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct Identifier(pub String);
 
 impl Parse for Identifier {
@@ -19,7 +19,7 @@ impl Parse for Identifier {
         if id.is_empty() || KEYWORDS.contains(&id.as_str()) {
             return Err(ParseError::SequenceNotFound {
                 expected: "valid identifier".into(),
-                received: id.into(),
+                received: id,
             });
         }
 
@@ -70,7 +70,7 @@ impl Parse for Binding {
         let s = tag(BIND_TOKEN, &s)?;
 
         let (_, s) = extract_whitespace(&s);
-        let (s, immutable) = dbg!(Immutable::parse(&s).unwrap());
+        let (s, immutable) = Immutable::parse(&s).unwrap();
 
         let (s, ident) = Identifier::parse(&s)?;
 
@@ -114,7 +114,12 @@ impl Parse for BindingRef {
 impl Eval for BindingRef {
     #[inline]
     fn eval(&self, env: &mut crate::env::Env) -> Result<crate::val::Val, crate::EvalError> {
-        env.get_stored_binding(&self.id).map(|(v, _)| v)
+        match env.get_stored(&self.id) {
+            Err(crate::EvalError::IdentifierNotFound(id)) => {
+                builtins::lookup_value(&id, env).ok_or(crate::EvalError::IdentifierNotFound(id))
+            }
+            stored => stored?.into_val(),
+        }
     }
 }
 
@@ -194,6 +199,8 @@ mod tests {
         assert_eq!(
             Reassignment {
                 lhs: "x".into(),
+                fields: vec![],
+                op: None,
                 rhs: Expr::simple(crate::lit::Literal::Bool(LitBool(true)))
             }
             .eval(&mut env),
@@ -1,7 +1,6 @@
 use crate::{
     Eval, Parse,
-    env::Env,
-    stmt::Stmt,
+    stmt::{Stmt, parse_stmts},
     utils::{extract_whitespace, tag},
 };
 
@@ -18,14 +17,7 @@ impl Parse for Block {
         let (_, s) = extract_whitespace(s);
         let s = tag(BLOCK_OPEN, &s)?;
 
-        let mut stmts = vec![];
-        let mut s = s;
-
-        while let Ok((new_s, stmt)) = Stmt::parse(&s) {
-            s = extract_whitespace(&new_s).1;
-            stmts.push(stmt);
-        }
-
+        let (s, stmts) = parse_stmts(&s, |s| s.starts_with(BLOCK_CLOSE))?;
         let s = tag(BLOCK_CLOSE, &s)?;
 
         Ok((s, Self { stmts }))
@@ -38,20 +30,22 @@ impl Eval for Block {
             return Ok(crate::val::Val::Unit);
         }
 
-        let mut this_env = Env::from_parent(env);
+        env.with_child(|this_env| {
+            let all_but_last = &self.stmts[..self.stmts.len() - 1];
 
-        let all_but_last = &self.stmts[..self.stmts.len() - 1];
-
-        for stmt in all_but_last {
-            stmt.eval(&mut this_env)?;
-        }
+            for stmt in all_but_last {
+                stmt.eval(this_env)?;
+            }
 
-        let last = self.stmts.last().unwrap(); // this is ok because we checked if it's empty at the beginning
-        Ok(last.eval(&mut this_env)?)
+            let last = self.stmts.last().unwrap(); // this is ok because we checked if it's empty at the beginning
+            last.eval(this_env)
+        })
     }
 }
 
 #[cfg(test)]
+// the literals are arbitrary test data, not stand-ins for the constants
+#[allow(clippy::approx_constant)]
 mod tests {
     use crate::{
         Eval, Parse, binding::Binding, block::Block, env::Env, expr::Expr, lit::Op, stmt::Stmt,
@@ -85,7 +79,7 @@ mod tests {
     #[test]
     fn parse_multiple_stmt_block() {
         assert_eq!(
-            Block::parse("{ bind x = 5 bind y = x y }"),
+            Block::parse("{ bind x = 5; bind y = x\n    y }"),
             Ok((
                 "".into(),
                 Block {
@@ -113,6 +107,17 @@ mod tests {
         )
     }
 
+    #[test]
+    fn parse_block_reports_broken_stmt() {
+        assert_eq!(
+            Block::parse("{ bind x = 5; bind = 4 }"),
+            Err(crate::ParseError::SequenceNotFound {
+                expected: "valid identifier".into(),
+                received: "".into()
+            })
+        )
+    }
+
     #[test]
     fn eval_empty_block() {
         assert_eq!(
@@ -175,8 +180,24 @@ mod tests {
                     id: "outer".into()
                 }))]
             }
-            .eval(&mut Env::from_parent(&external_env)),
+            .eval(&mut Env::from_parent(external_env)),
             Ok(Val::Real(3.14159))
         )
     }
+
+    #[test]
+    fn eval_block_reassigns_outer_binding() {
+        let mut env = Env::new();
+        let (_, stmt) = Stmt::parse("bind x = 0").unwrap();
+        stmt.eval(&mut env).unwrap();
+
+        let (_, block) = Block::parse("{ x += 1 }").unwrap();
+        block.eval(&mut env).unwrap();
+        block.eval(&mut env).unwrap();
+
+        assert_eq!(
+            env.get_stored_binding(&"x".into()),
+            Ok((Val::Real(2.), false))
+        )
+    }
 }
@@ -0,0 +1,147 @@
+use crate::{
+    Env, EvalError,
+    binding::Identifier,
+    fn_call::{CallArgs, expect_args},
+    math,
+    val::Val,
+};
+
+pub(crate) type Builtin = fn(CallArgs) -> Result<Val, EvalError>;
+
+/// Looks up the built-in function `id`. Built-ins are available everywhere, including inside
+/// function bodies, but user definitions of the same name take precedence. The file functions
+/// only exist in programs allowed to use them, see [`Env::allow_file_access`].
+pub(crate) fn lookup(id: &Identifier, env: &mut Env) -> Option<Builtin> {
+    Some(match id.0.as_str() {
+        "Ok" => ok,
+        "Err" => err,
+        "parseReal" => parse_real,
+        "readFile" if env.module_ctx().file_access() => read_file,
+        "writeFile" if env.module_ctx().file_access() => write_file,
+        _ => return None,
+    })
+}
+
+/// Looks up the built-in value `id`, such as the `math` module. Like built-in functions, these are
+/// available everywhere, unless something else has the same name.
+pub(crate) fn lookup_value(id: &Identifier, env: &mut Env) -> Option<Val> {
+    match id.0.as_str() {
+        math::MODULE_NAME => Some(Val::Module(env.module_ctx().math())),
+        _ => None,
+    }
+}
+
+fn expect_str(val: Val) -> Result<String, EvalError> {
+    match val {
+        Val::Str(s) => Ok(s),
+        v => Err(EvalError::InvalidType {
+            expected: "a string".into(),
+            received: v.get_type().into(),
+        }),
+    }
+}
+
+fn ok(args: CallArgs) -> Result<Val, EvalError> {
+    let [val] = expect_args(args, ["val"])?;
+    Ok(Val::ok(val))
+}
+
+fn err(args: CallArgs) -> Result<Val, EvalError> {
+    let [val] = expect_args(args, ["val"])?;
+    Ok(Val::err(val))
+}
+
+/// `parseReal(s)`, which parses `s` as a real number.
+fn parse_real(args: CallArgs) -> Result<Val, EvalError> {
+    let [s] = expect_args(args, ["s"])?;
+    let s = expect_str(s)?;
+
+    Ok(match s.trim().parse() {
+        Ok(r) => Val::ok(Val::Real(r)),
+        Err(_) => Val::err_with("ParseError", format!("{s:?} is not a real number")),
+    })
+}
+
+/// `readFile(path)`, which reads the file at `path` to a string.
+fn read_file(args: CallArgs) -> Result<Val, EvalError> {
+    let [path] = expect_args(args, ["path"])?;
+
+    Ok(match std::fs::read_to_string(expect_str(path)?) {
+        Ok(contents) => Val::ok(Val::Str(contents)),
+        Err(e) => Val::err_with("IoError", e.to_string()),
+    })
+}
+
+/// `writeFile(path, contents)`, which replaces the contents of the file at `path`.
+fn write_file(args: CallArgs) -> Result<Val, EvalError> {
+    let [path, contents] = expect_args(args, ["path", "contents"])?;
+
+    Ok(
+        match std::fs::write(expect_str(path)?, expect_str(contents)?) {
+            Ok(()) => Val::ok(Val::Unit),
+            Err(e) => Val::err_with("IoError", e.to_string()),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Env, EvalError,
+        test_utils::{Fixture, eval_in, eval_src},
+        val::Val,
+    };
+
+    /// Evaluates `src` in a program that may use files.
+    fn eval_with_files(src: &str) -> Result<Val, EvalError> {
+        let mut env = Env::new();
+        env.allow_file_access();
+        eval_in(src, &mut env)
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let fixture = Fixture::new(&[]);
+        let path = fixture.path("round_trip.txt");
+        let path = path.to_str().unwrap();
+
+        assert_eq!(
+            eval_with_files(&format!(
+                "writeFile({path:?}, \"hello\")?; readFile(path: {path:?})"
+            )),
+            Ok(Val::ok(Val::Str("hello".into())))
+        )
+    }
+
+    #[test]
+    fn file_access_is_opt_in() {
+        assert_eq!(
+            eval_src("readFile(\"/etc/hostname\")"),
+            Err(EvalError::IdentifierNotFound("readFile".into()))
+        );
+        assert_eq!(
+            eval_src("writeFile(\"out.txt\", \"\")"),
+            Err(EvalError::IdentifierNotFound("writeFile".into()))
+        )
+    }
+
+    #[test]
+    fn read_missing_file() {
+        let Ok(Val::Result(res)) = eval_with_files("readFile(\"/this/does/not/exist\")") else {
+            panic!("expected a result")
+        };
+
+        let Err(Val::Error(e)) = *res else {
+            panic!("expected an error")
+        };
+        assert_eq!(e.kind, "IoError")
+    }
+
+    #[test]
+    fn builtins_can_be_shadowed() {
+        assert_eq!(
+            eval_src("func parseReal(s) => 0; parseReal(\"1\")"),
+            Ok(Val::Real(0.))
+        )
+    }
+}
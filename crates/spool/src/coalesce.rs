@@ -0,0 +1,95 @@
+use crate::{
+    Eval, EvalError, Parse,
+    expr::Expr,
+    utils::{extract_whitespace, tag},
+    val::Val,
+};
+
+const COALESCE_TOKEN: &str = "??";
+
+/// `lhs ?? rhs`, which evaluates to `lhs` unless it's `nil`, and to `rhs` otherwise. `rhs` is
+/// only evaluated if it's needed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CoalesceExpr {
+    pub lhs: Expr,
+    pub rhs: Expr,
+}
+
+impl CoalesceExpr {
+    /// Parses `?? rhs`, following the already parsed left hand side. `??` binds loosest of all
+    /// operators, and groups to the right, so `a ?? b ?? c` tries `a`, then `b`, then `c`.
+    pub(crate) fn parse_rhs(s: &str) -> crate::ParseOutput<Expr> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(COALESCE_TOKEN, &s)?;
+
+        Expr::parse(&s)
+    }
+}
+
+impl Eval for CoalesceExpr {
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, EvalError> {
+        match self.lhs.eval(env)? {
+            Val::Nil => self.rhs.eval(env),
+            v => Ok(v),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvalError, test_utils::eval_src, val::Val};
+
+    #[test]
+    fn coalesce() {
+        assert_eq!(eval_src("nil ?? 1"), Ok(Val::Real(1.)));
+        assert_eq!(eval_src("false ?? 1"), Ok(Val::Bool(false)));
+        assert_eq!(eval_src("nil ?? nil ?? 1 + 1"), Ok(Val::Real(2.)))
+    }
+
+    #[test]
+    fn coalesce_is_lazy() {
+        assert_eq!(eval_src("1 ?? undefined"), Ok(Val::Real(1.)))
+    }
+
+    #[test]
+    fn optional_chaining() {
+        let src = "struct Node { value, next = nil }
+            bind list = Node(1, Node(2))";
+
+        assert_eq!(
+            eval_src(&format!("{src}; list?.next?.value")),
+            Ok(Val::Real(2.))
+        );
+        assert_eq!(
+            eval_src(&format!("{src}; list?.next?.next?.value")),
+            Ok(Val::Nil)
+        );
+        assert_eq!(
+            eval_src(&format!("{src}; list?.missing ?? 0")),
+            Ok(Val::Real(0.))
+        );
+        assert_eq!(
+            eval_src(&format!("{src}; list.next.next.value")),
+            Err(EvalError::UnknownMember {
+                ty: "nil".into(),
+                member: "value".into()
+            })
+        )
+    }
+
+    #[test]
+    fn optional_map_lookup() {
+        assert_eq!(
+            eval_src("bind m = [\"a\": [\"b\": 1]]; [m?.a?.b, m?.x?.b, m.a.b]"),
+            Ok(Val::List(vec![Val::Real(1.), Val::Nil, Val::Real(1.)]))
+        );
+        assert_eq!(
+            eval_src("bind m = [\"a\": [\"b\": 1]]; [m?.c.d, m?.c.d.len(), m.a?.c.d]"),
+            Ok(Val::List(vec![Val::Nil, Val::Nil, Val::Nil]))
+        );
+        assert_eq!(
+            eval_src("[\"a\": 1].b"),
+            Err(EvalError::KeyNotFound("b".into()))
+        )
+    }
+}
@@ -0,0 +1,450 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    EvalError,
+    binding::Identifier,
+    func::Param,
+    structs::{Instance, StructDef},
+    val::Val,
+};
+
+/// Converts a Rust value into a Spool value.
+///
+/// Spool numbers are `f32`, so numbers are rounded to the nearest `f32`. Integers of more than 24
+/// bits, such as `i64`, `u64` and `usize`, and `f64` values lose precision: `16_777_217_i64`
+/// becomes `16777216`.
+pub trait IntoVal {
+    fn into_val(self) -> Val;
+}
+
+/// Extracts a Rust value from a Spool value, failing with [`EvalError::InvalidType`] if it has
+/// the wrong type.
+pub trait FromVal: Sized {
+    fn from_val(val: Val) -> Result<Self, EvalError>;
+}
+
+pub(crate) fn invalid_type<T>(expected: &str, received: &Val) -> Result<T, EvalError> {
+    Err(EvalError::InvalidType {
+        expected: expected.into(),
+        received: received.get_type().into(),
+    })
+}
+
+impl Val {
+    /// Creates an instance of a struct called `name`, which has just `fields` and no methods.
+    pub fn new_struct(name: &str, fields: impl IntoIterator<Item = (String, Val)>) -> Self {
+        let fields: BTreeMap<Identifier, Val> = fields
+            .into_iter()
+            .map(|(k, v)| (Identifier(k), v))
+            .collect();
+
+        let def = StructDef {
+            id: Identifier(name.into()),
+            fields: fields
+                .keys()
+                .map(|id| Param {
+                    id: id.clone(),
+                    default: None,
+                    variadic: false,
+                })
+                .collect(),
+            methods: HashMap::new(),
+        };
+
+        Self::Struct(Arc::new(Mutex::new(Instance {
+            def: Arc::new(def),
+            fields,
+        })))
+    }
+}
+
+/// The fields of a struct instance or map, which a derived [`FromVal`] converts one by one.
+pub struct Fields {
+    ty: String,
+    fields: BTreeMap<String, Val>,
+}
+
+impl Fields {
+    /// Reads the fields of `val`, which should be converted to the type `ty`. Any struct with
+    /// the right fields will do, as will a map.
+    pub fn from_val(ty: &str, val: Val) -> Result<Self, EvalError> {
+        let fields = match val {
+            Val::Struct(inst) => inst
+                .lock()
+                .unwrap()
+                .fields
+                .iter()
+                .map(|(k, v)| (k.0.clone(), v.clone()))
+                .collect(),
+            Val::Map(map) => map,
+            v => return invalid_type(&format!("a struct or map for {ty}"), &v),
+        };
+
+        Ok(Self {
+            ty: ty.into(),
+            fields,
+        })
+    }
+
+    /// Reads the name of the variant `val` holds, and its fields unless it's a unit variant,
+    /// which is just its name as a string.
+    pub fn variant(ty: &str, val: Val) -> Result<(String, Option<Self>), EvalError> {
+        match val {
+            Val::Str(name) => Ok((name, None)),
+            Val::Struct(ref inst) => {
+                let name = inst.lock().unwrap().def.id.0.clone();
+                Ok((name.clone(), Some(Self::from_val(&name, val)?)))
+            }
+            v => invalid_type(&format!("a variant of {ty}"), &v),
+        }
+    }
+
+    /// Converts the field called `field`. A missing field is treated as `nil`, so that it can
+    /// be converted to `None`.
+    pub fn take<T: FromVal>(&mut self, field: &str) -> Result<T, EvalError> {
+        match self.fields.remove(field) {
+            Some(v) => T::from_val(v).map_err(|error| EvalError::InvalidField {
+                ty: self.ty.clone(),
+                field: field.into(),
+                error: Box::new(error),
+            }),
+            None => T::from_val(Val::Nil).map_err(|_| EvalError::MissingField {
+                ty: self.ty.clone(),
+                field: field.into(),
+            }),
+        }
+    }
+}
+
+impl IntoVal for Val {
+    fn into_val(self) -> Val {
+        self
+    }
+}
+
+impl FromVal for Val {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        Ok(val)
+    }
+}
+
+macro_rules! impl_float {
+    ($($ty:ty),*) => {$(
+        impl IntoVal for $ty {
+            fn into_val(self) -> Val {
+                Val::Real(self as f32)
+            }
+        }
+
+        impl FromVal for $ty {
+            fn from_val(val: Val) -> Result<Self, EvalError> {
+                match val {
+                    Val::Real(r) => Ok(r as $ty),
+                    v => invalid_type("a real number", &v),
+                }
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_int {
+    ($($ty:ty),*) => {$(
+        impl IntoVal for $ty {
+            fn into_val(self) -> Val {
+                Val::Real(self as f32)
+            }
+        }
+
+        impl FromVal for $ty {
+            fn from_val(val: Val) -> Result<Self, EvalError> {
+                // `MAX` rounds up to `MAX + 1` as an `f32` for the wider types, so the upper
+                // bound is exclusive. `MAX + 1` is a power of two, which `f32` represents exactly
+                let max = <$ty>::MAX as f32 + 1.;
+
+                match val {
+                    Val::Real(r) if r.fract() == 0. && r >= <$ty>::MIN as f32 && r < max => {
+                        Ok(r as $ty)
+                    }
+                    Val::Real(r) => Err(EvalError::InvalidType {
+                        expected: concat!("an integer that fits in ", stringify!($ty)).into(),
+                        received: r.to_string(),
+                    }),
+                    v => invalid_type("an integer", &v),
+                }
+            }
+        }
+    )*};
+}
+
+impl_float!(f32, f64);
+impl_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl IntoVal for String {
+    fn into_val(self) -> Val {
+        Val::Str(self)
+    }
+}
+
+impl IntoVal for &str {
+    fn into_val(self) -> Val {
+        Val::Str(self.into())
+    }
+}
+
+impl FromVal for String {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        match val {
+            Val::Str(s) => Ok(s),
+            v => invalid_type("a string", &v),
+        }
+    }
+}
+
+impl IntoVal for bool {
+    fn into_val(self) -> Val {
+        Val::Bool(self)
+    }
+}
+
+impl FromVal for bool {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        match val {
+            Val::Bool(b) => Ok(b),
+            v => invalid_type("a boolean", &v),
+        }
+    }
+}
+
+impl IntoVal for () {
+    fn into_val(self) -> Val {
+        Val::Unit
+    }
+}
+
+impl FromVal for () {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        match val {
+            Val::Unit => Ok(()),
+            v => invalid_type("()", &v),
+        }
+    }
+}
+
+/// `None` is `nil`, and `nil` is the only value that converts to `None`.
+impl<T: IntoVal> IntoVal for Option<T> {
+    fn into_val(self) -> Val {
+        match self {
+            Some(v) => v.into_val(),
+            None => Val::Nil,
+        }
+    }
+}
+
+impl<T: FromVal> FromVal for Option<T> {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        match val {
+            Val::Nil => Ok(None),
+            v => T::from_val(v).map(Some),
+        }
+    }
+}
+
+impl<T: IntoVal> IntoVal for Box<T> {
+    fn into_val(self) -> Val {
+        (*self).into_val()
+    }
+}
+
+impl<T: FromVal> FromVal for Box<T> {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        T::from_val(val).map(Box::new)
+    }
+}
+
+impl<T: IntoVal> IntoVal for Vec<T> {
+    fn into_val(self) -> Val {
+        Val::List(self.into_iter().map(IntoVal::into_val).collect())
+    }
+}
+
+impl<T: FromVal> FromVal for Vec<T> {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        match val {
+            Val::List(items) => items.into_iter().map(T::from_val).collect(),
+            v => invalid_type("a list", &v),
+        }
+    }
+}
+
+impl<T: IntoVal> IntoVal for HashMap<String, T> {
+    fn into_val(self) -> Val {
+        Val::Map(self.into_iter().map(|(k, v)| (k, v.into_val())).collect())
+    }
+}
+
+impl<T: FromVal> FromVal for HashMap<String, T> {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        match val {
+            Val::Map(map) => map
+                .into_iter()
+                .map(|(k, v)| Ok((k, T::from_val(v)?)))
+                .collect(),
+            v => invalid_type("a map", &v),
+        }
+    }
+}
+
+impl<T: IntoVal> IntoVal for BTreeMap<String, T> {
+    fn into_val(self) -> Val {
+        Val::Map(self.into_iter().map(|(k, v)| (k, v.into_val())).collect())
+    }
+}
+
+impl<T: FromVal> FromVal for BTreeMap<String, T> {
+    fn from_val(val: Val) -> Result<Self, EvalError> {
+        match val {
+            Val::Map(map) => map
+                .into_iter()
+                .map(|(k, v)| Ok((k, T::from_val(v)?)))
+                .collect(),
+            v => invalid_type("a map", &v),
+        }
+    }
+}
+
+/// Tuples are lists of a fixed length.
+macro_rules! impl_tuple {
+    ($len:literal; $($name:ident),+) => {
+        impl<$($name: IntoVal),+> IntoVal for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_val(self) -> Val {
+                let ($($name,)+) = self;
+                Val::List(vec![$($name.into_val()),+])
+            }
+        }
+
+        impl<$($name: FromVal),+> FromVal for ($($name,)+) {
+            fn from_val(val: Val) -> Result<Self, EvalError> {
+                match val {
+                    Val::List(items) if items.len() == $len => {
+                        let mut items = items.into_iter();
+                        // the length was checked above
+                        Ok(($($name::from_val(items.next().unwrap())?,)+))
+                    }
+                    Val::List(items) => Err(EvalError::InvalidType {
+                        expected: concat!("a list of ", $len, " elements").into(),
+                        received: format!("a list of {} elements", items.len()),
+                    }),
+                    v => invalid_type(concat!("a list of ", $len, " elements"), &v),
+                }
+            }
+        }
+    };
+}
+
+impl_tuple!(1; A);
+impl_tuple!(2; A, B);
+impl_tuple!(3; A, B, C);
+impl_tuple!(4; A, B, C, D);
+impl_tuple!(5; A, B, C, D, E);
+impl_tuple!(6; A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{EvalError, FromVal, Interpreter, IntoVal, Val};
+
+    #[test]
+    fn round_trip() {
+        let map = HashMap::from([("a".to_string(), vec![1, 2]), ("b".into(), vec![])]);
+        assert_eq!(
+            HashMap::<String, Vec<i32>>::from_val(map.clone().into_val()),
+            Ok(map)
+        );
+
+        let tuple = ("x".to_string(), 1.5f64, true, Some(3u8), None::<String>, ());
+        assert_eq!(<_>::from_val(tuple.clone().into_val()), Ok(tuple));
+    }
+
+    #[test]
+    fn wide_numbers_lose_precision() {
+        assert_eq!(16_777_217_i64.into_val(), Val::Real(16_777_216.));
+        assert_eq!(0.1f64.into_val(), Val::Real(0.1));
+        assert_ne!(f64::from_val(0.1f64.into_val()), Ok(0.1))
+    }
+
+    #[test]
+    fn typed_errors() {
+        assert_eq!(
+            String::from_val(Val::Real(1.)),
+            Err(EvalError::InvalidType {
+                expected: "a string".into(),
+                received: "Real number".into()
+            })
+        );
+        assert_eq!(
+            u8::from_val(Val::Real(-1.)),
+            Err(EvalError::InvalidType {
+                expected: "an integer that fits in u8".into(),
+                received: "-1".into()
+            })
+        );
+        assert_eq!(
+            i32::from_val(Val::Real(1.5)),
+            Err(EvalError::InvalidType {
+                expected: "an integer that fits in i32".into(),
+                received: "1.5".into()
+            })
+        );
+        assert_eq!(
+            i32::from_val(Val::Real(2147483648.)),
+            Err(EvalError::InvalidType {
+                expected: "an integer that fits in i32".into(),
+                received: "2147483600".into()
+            })
+        );
+        assert_eq!(i32::from_val(Val::Real(-2147483648.)), Ok(i32::MIN));
+        assert_eq!(u8::from_val(Val::Real(255.)), Ok(255));
+        assert!(u64::from_val(Val::Real(18446744073709551616.)).is_err());
+        assert_eq!(
+            <(f32, f32)>::from_val(vec![1., 2., 3.].into_val()),
+            Err(EvalError::InvalidType {
+                expected: "a list of 2 elements".into(),
+                received: "a list of 3 elements".into()
+            })
+        );
+        assert_eq!(
+            Vec::<bool>::from_val(vec![true.into_val(), "no".into_val()].into_val()),
+            Err(EvalError::InvalidType {
+                expected: "a boolean".into(),
+                received: "String".into()
+            })
+        )
+    }
+
+    #[test]
+    fn typed_native() {
+        let mut interp = Interpreter::new();
+        interp.register_native("repeat", 2, |args| {
+            let s = String::from_val(args[0].clone())?;
+            let n = usize::from_val(args[1].clone())?;
+            Ok(s.repeat(n).into_val())
+        });
+
+        assert_eq!(
+            interp.eval_str("repeat(\"ab\", 2)"),
+            Ok(Val::Str("abab".into()))
+        );
+        assert_eq!(
+            interp.eval_str("repeat(\"ab\", 0.5)"),
+            Err(EvalError::InvalidType {
+                expected: "an integer that fits in usize".into(),
+                received: "0.5".into()
+            })
+        )
+    }
+}
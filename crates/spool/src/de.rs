@@ -0,0 +1,527 @@
+use std::{cell::RefCell, collections::BTreeMap, fmt::Display, path::Path, sync::Arc};
+
+use serde::{
+    Deserializer,
+    de::{
+        self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+        SeqAccess, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::{Env, EvalError, Interpreter, env::Storeable, val::Val};
+
+thread_local! {
+    /// The instances being deserialized further up the stack of the current thread.
+    static DESERIALIZING: RefCell<Vec<usize>> = const { RefCell::new(vec![]) };
+}
+
+/// An error from deserializing a Spool value into a Rust type.
+#[derive(Debug, PartialEq, Clone)]
+pub enum DeError {
+    /// The script failed to evaluate.
+    Eval(EvalError),
+    /// The value at `path` doesn't fit the Rust type. The path leads from the binding to the
+    /// value, as in `server.ports[0]`, and is empty for the value as a whole.
+    Invalid { path: String, message: String },
+}
+
+impl DeError {
+    /// Prepends `segment`, a binding, field or index, to the path of this error.
+    fn within(self, segment: &str) -> Self {
+        match self {
+            Self::Invalid { path, message } => Self::Invalid {
+                path: match path.is_empty() || path.starts_with('[') {
+                    true => format!("{segment}{path}"),
+                    false => format!("{segment}.{path}"),
+                },
+                message,
+            },
+            e => e,
+        }
+    }
+}
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Eval(e) => write!(f, "{e}"),
+            Self::Invalid { path, message } if path.is_empty() => write!(f, "{message}"),
+            Self::Invalid { path, message } => write!(f, "`{path}`: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Invalid {
+            path: String::new(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// Deserializes `T` from `val`. Struct instances and maps deserialize into structs, lists into
+/// sequences, and `nil` into `None`. Enum variants are either a string for unit variants, or a
+/// struct instance named after the variant.
+pub fn from_val<T: DeserializeOwned>(val: Val) -> Result<T, DeError> {
+    T::deserialize(val)
+}
+
+/// Deserializes `T` from the bindings in the scope `env`, by name. Functions and structs are
+/// left out.
+///
+/// Spool names can't contain `_`, so `snake_case` fields of `T` have to be renamed to match the
+/// bindings, usually with `#[serde(rename_all = "camelCase")]`. The same goes for the fields of
+/// struct instances.
+pub fn from_env<T: DeserializeOwned>(env: &Env) -> Result<T, DeError> {
+    let globals: BTreeMap<String, Val> = env
+        .store
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|(k, v)| match v {
+            Storeable::Binding(v, _) => Some((k.0.clone(), v.clone())),
+            _ => None,
+        })
+        .collect();
+
+    from_val(Val::Map(globals))
+}
+
+/// Evaluates the script at `path`, and deserializes `T` from its globals, like a config file. See
+/// [`from_env`] for how fields are named.
+pub fn from_file<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, DeError> {
+    let mut interp = Interpreter::new();
+    interp.eval_file(path).map_err(DeError::Eval)?;
+
+    from_env(interp.env())
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for Val {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Val {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Self::Str(s) => visitor.visit_string(s),
+            // integral numbers are visited as integers, so that they fit integer fields
+            Self::Real(r) if r.fract() == 0. && r.abs() < i64::MAX as f32 => {
+                visitor.visit_i64(r as i64)
+            }
+            Self::Real(r) => visitor.visit_f32(r),
+            Self::Bool(b) => visitor.visit_bool(b),
+            Self::List(items) => visitor.visit_seq(Seq {
+                items: items.into_iter().enumerate(),
+            }),
+            Self::Map(map) => visitor.visit_map(Map::new(map)),
+            Self::Struct(inst) => {
+                // an instance that contains itself would be deserialized forever
+                let ptr = Arc::as_ptr(&inst) as usize;
+                if DESERIALIZING.with(|d| d.borrow().contains(&ptr)) {
+                    return Err(de::Error::custom("cyclic struct instance"));
+                }
+
+                let fields = inst
+                    .lock()
+                    .unwrap()
+                    .fields
+                    .iter()
+                    .map(|(k, v)| (k.0.clone(), v.clone()))
+                    .collect();
+
+                DESERIALIZING.with(|d| d.borrow_mut().push(ptr));
+                let out = visitor.visit_map(Map::new(fields));
+                DESERIALIZING.with(|d| d.borrow_mut().pop());
+
+                out
+            }
+            Self::Error(e) => visitor.visit_map(Map::new(BTreeMap::from([
+                ("kind".into(), Val::Str(e.kind)),
+                ("message".into(), Val::Str(e.message)),
+            ]))),
+            Self::Result(res) => match *res {
+                Ok(v) => visitor.visit_map(Map::new(BTreeMap::from([("Ok".into(), v)]))),
+                Err(v) => visitor.visit_map(Map::new(BTreeMap::from([("Err".into(), v)]))),
+            },
+            Self::Nil => visitor.visit_none(),
+            Self::Unit => visitor.visit_unit(),
+            v @ (Self::Range(_) | Self::Func(_) | Self::Iter(_) | Self::Module(_)) => {
+                Err(de::Error::custom(format!(
+                    "a value of type {} can't be deserialized",
+                    v.get_type()
+                )))
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Self::Nil => visitor.visit_none(),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Self::Nil | Self::Unit => visitor.visit_unit(),
+            v => v.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let (variant, content) = match self {
+            Self::Str(s) => (s, None),
+            Self::Struct(ref inst) => (inst.lock().unwrap().def.id.0.clone(), Some(self.clone())),
+            Self::Result(res) => match *res {
+                Ok(v) => ("Ok".into(), Some(v)),
+                Err(v) => ("Err".into(), Some(v)),
+            },
+            Self::Map(map) if map.len() == 1 => {
+                // the map has exactly one entry
+                let (k, v) = map.into_iter().next().unwrap();
+                (k, Some(v))
+            }
+            v => {
+                return Err(de::Error::custom(format!(
+                    "expected a string or struct for an enum, received {}",
+                    v.get_type()
+                )));
+            }
+        };
+
+        visitor.visit_enum(Enum { variant, content })
+    }
+
+    /// Skips bindings the Rust type has no field for, even ones that can't be deserialized,
+    /// like imported modules.
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+struct Seq<I> {
+    items: I,
+}
+
+impl<'de, I: Iterator<Item = (usize, Val)>> SeqAccess<'de> for Seq<I> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeError> {
+        match self.items.next() {
+            Some((idx, v)) => seed
+                .deserialize(v)
+                .map(Some)
+                .map_err(|e| e.within(&format!("[{idx}]"))),
+            None => Ok(None),
+        }
+    }
+}
+
+struct Map {
+    entries: std::collections::btree_map::IntoIter<String, Val>,
+    /// The entry whose key was visited last, and whose value is visited next.
+    next: Option<(String, Val)>,
+}
+
+impl Map {
+    fn new(map: BTreeMap<String, Val>) -> Self {
+        Self {
+            entries: map.into_iter(),
+            next: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for Map {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        let Some((k, v)) = self.entries.next() else {
+            return Ok(None);
+        };
+
+        let key = seed.deserialize(Val::Str(k.clone()))?;
+        self.next = Some((k, v));
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let (k, v) = self
+            .next
+            .take()
+            .ok_or_else(|| de::Error::custom("value visited before its key"))?;
+
+        seed.deserialize(v).map_err(|e| e.within(&k))
+    }
+}
+
+struct Enum {
+    variant: String,
+    content: Option<Val>,
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), DeError> {
+        let variant = seed.deserialize(Val::Str(self.variant.clone()))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Enum {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        match self.content {
+            None | Some(Val::Nil | Val::Unit) => Ok(()),
+            Some(v) => Err(de::Error::custom(format!(
+                "expected the unit variant {}, received {}",
+                self.variant,
+                v.get_type()
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, DeError> {
+        seed.deserialize(self.content.unwrap_or(Val::Nil))
+            .map_err(|e| e.within(&self.variant))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, DeError> {
+        self.content
+            .unwrap_or(Val::Nil)
+            .deserialize_any(visitor)
+            .map_err(|e| e.within(&self.variant))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.content
+            .unwrap_or(Val::Nil)
+            .deserialize_any(visitor)
+            .map_err(|e| e.within(&self.variant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::{EvalError, Interpreter, Val, test_utils::Fixture};
+
+    use super::{DeError, from_env, from_file, from_val};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        server: Server,
+        #[serde(default)]
+        features: Vec<Feature>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Server {
+        port: u16,
+        hosts: Vec<String>,
+        timeout: Option<f32>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Feature {
+        Logging,
+        Cache { size: u32 },
+    }
+
+    fn eval_config(src: &str) -> Result<Config, DeError> {
+        let mut interp = Interpreter::new();
+        interp.eval_str(src).unwrap();
+        from_env(interp.env())
+    }
+
+    #[test]
+    fn deserialize_globals() {
+        assert_eq!(
+            eval_config(
+                "struct Cache { size }
+                func hosts(n) => { 1..=n }.map(func(i) => \"node\").collect()
+                bind name = \"app\"
+                bind base = 8000
+                bind server = [\"port\": base + 80, \"hosts\": hosts(2), \"timeout\": nil]
+                bind features = [\"Logging\", Cache(64)]"
+            ),
+            Ok(Config {
+                name: "app".into(),
+                server: Server {
+                    port: 8080,
+                    hosts: vec!["node".into(), "node".into()],
+                    timeout: None
+                },
+                features: vec![Feature::Logging, Feature::Cache { size: 64 }]
+            })
+        )
+    }
+
+    #[test]
+    fn errors_report_path() {
+        assert_eq!(
+            eval_config("bind name = \"app\"; bind server = [\"port\": 80, \"hosts\": [\"a\", 1]]"),
+            Err(DeError::Invalid {
+                path: "server.hosts[1]".into(),
+                message: "invalid type: integer `1`, expected a string".into()
+            })
+        );
+        assert_eq!(
+            eval_config("bind name = \"app\"; bind server = [\"hosts\": []]"),
+            Err(DeError::Invalid {
+                path: "server".into(),
+                message: "missing field `port`".into()
+            })
+        );
+        assert_eq!(
+            eval_config("bind name = \"app\"; bind server = [\"port\": 1.5, \"hosts\": []]")
+                .map_err(|e| e.to_string()),
+            Err("`server.port`: invalid type: floating point `1.5`, expected u16".into())
+        )
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Limits {
+        max_retries: u32,
+        retry: Retry,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Retry {
+        backoff_ms: u32,
+    }
+
+    #[test]
+    fn deserialize_renamed_fields() {
+        let mut interp = Interpreter::new();
+        interp
+            .eval_str(
+                "struct Retry { backoffMs }
+                bind maxRetries = 3
+                bind retry = Retry(250)",
+            )
+            .unwrap();
+
+        assert_eq!(
+            from_env(interp.env()),
+            Ok(Limits {
+                max_retries: 3,
+                retry: Retry { backoff_ms: 250 }
+            })
+        )
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Node {
+        next: Option<Box<Node>>,
+    }
+
+    #[test]
+    fn deserialize_cyclic_instance() {
+        let val = Interpreter::new()
+            .eval_str("struct N { next = nil }; bind a = N(); bind b = N(a); a.next = b; a")
+            .unwrap();
+
+        assert_eq!(
+            from_val::<Node>(val),
+            Err(DeError::Invalid {
+                path: "next.next".into(),
+                message: "cyclic struct instance".into()
+            })
+        )
+    }
+
+    #[test]
+    fn deserialize_returned_map() {
+        let val = Interpreter::new()
+            .eval_str("[\"port\": 1, \"hosts\": [], \"timeout\": 2.5]")
+            .unwrap();
+
+        assert_eq!(
+            from_val::<Server>(val),
+            Ok(Server {
+                port: 1,
+                hosts: vec![],
+                timeout: Some(2.5)
+            })
+        );
+        assert!(from_val::<Server>(Val::Real(1.)).is_err())
+    }
+
+    #[test]
+    fn deserialize_file() {
+        let files = Fixture::new(&[
+            ("ports.spool", "pub func port(n) => 8000 + n"),
+            (
+                "app.spool",
+                "import ports
+                bind name = \"app\"
+                bind server = [\"port\": ports.port(1), \"hosts\": [\"localhost\"]]",
+            ),
+        ]);
+
+        assert_eq!(
+            from_file::<Config>(files.path("app.spool")),
+            Ok(Config {
+                name: "app".into(),
+                server: Server {
+                    port: 8001,
+                    hosts: vec!["localhost".into()],
+                    timeout: None
+                },
+                features: vec![]
+            })
+        );
+        assert_eq!(
+            from_file::<Config>(files.path("nope.spool")),
+            Err(DeError::Eval(EvalError::ModuleNotFound(
+                files.path("nope.spool").display().to_string()
+            )))
+        )
+    }
+}
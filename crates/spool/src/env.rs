@@ -1,89 +1,369 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, Weak},
+};
 
-use crate::{EvalError, binding::Identifier, expr::Expr, val::Val};
+use crate::{
+    EvalError, binding::Identifier, expr::Expr, func::Param, module::ModuleCtx,
+    native::NativeFunction, structs::StructDef, val::Val,
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Storeable {
     Binding(Val, bool),
     Func(NamelessFunction),
+    Struct(Arc<StructDef>),
+    Native(NativeFunction),
+}
+
+impl Storeable {
+    /// Returns the value of a binding, or a stored function as a function value.
+    pub(crate) fn into_val(self) -> Result<Val, EvalError> {
+        match self {
+            Self::Binding(v, _) => Ok(v),
+            Self::Func(f) => Ok(Val::Func(f.into())),
+            Self::Struct(_) | Self::Native(_) => Err(EvalError::InvalidStoredType),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct NamelessFunction {
-    pub(crate) params: Vec<Identifier>,
+    pub(crate) params: Vec<Param>,
     pub(crate) body: Expr,
+    /// The top-level scope the function was defined in, if it's still alive.
+    pub(crate) globals: Option<Globals>,
+    /// The local scopes around a function expression, see [`Captured`].
+    pub(crate) captured: Captured,
+}
+
+pub type Store = Arc<Mutex<HashMap<Identifier, Storeable>>>;
+
+/// A handle to the top-level scope of a program or module. Function bodies run in a fresh scope
+/// that falls back to the globals of the module they were defined in, so that they can call each
+/// other no matter where they're called from.
+///
+/// The handle is weak, since the functions it's handed to are themselves stored in that scope.
+#[derive(Clone)]
+pub struct Globals {
+    store: Weak<Mutex<HashMap<Identifier, Storeable>>>,
+    module: Option<Arc<ModuleCtx>>,
+}
+
+impl PartialEq for Globals {
+    fn eq(&self, other: &Self) -> bool {
+        self.store.ptr_eq(&other.store)
+    }
+}
+
+impl std::fmt::Debug for Globals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Globals(..)")
+    }
+}
+
+/// The local scopes a function was defined in, outermost first. The function's body runs nested
+/// in them, so that it can use the bindings around it after those scopes have ended.
+///
+/// Unlike [`Globals`], the handles are strong, so a function defined or bound in a scope it
+/// captured keeps that scope alive through a reference cycle.
+#[derive(Clone, Default)]
+pub struct Captured(Vec<Store>);
+
+impl PartialEq for Captured {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl std::fmt::Debug for Captured {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Captured(..)")
+    }
+}
+
+/// A child scope created by [`Env::with_child`], which hands the scope it was created from back
+/// once it's dropped, so that a panic caught by an embedder doesn't leave that scope empty.
+struct ChildScope<'a> {
+    parent: &'a mut Env,
+    child: Env,
+}
+
+impl Drop for ChildScope<'_> {
+    fn drop(&mut self) {
+        // the child cannot have lost its parent, since it's never handed out by value
+        if let Some(parent) = self.child.parent.take() {
+            *self.parent = *parent;
+        }
+    }
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct Env<'p> {
-    pub store: HashMap<Identifier, Storeable>,
-    pub parent: Option<&'p Self>,
+/// A scope of bindings and functions. Child scopes own their parent, so that inner blocks can
+/// reassign bindings that were declared further up the chain.
+#[derive(Debug, Default)]
+pub struct Env {
+    pub store: Store,
+    pub parent: Option<Box<Self>>,
+    /// Set on the scope of a function body, see [`Globals`].
+    pub(crate) globals: Option<Globals>,
+    /// Set on the top-level scope of a file, see [`ModuleCtx`].
+    pub(crate) module: Option<Arc<ModuleCtx>>,
+    /// The names declared with `pub` in this scope, which a module exports.
+    pub(crate) exports: HashSet<Identifier>,
 }
 
-impl<'p> Env<'p> {
+impl Env {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn from_parent(parent: &'p Self) -> Self {
+    pub fn from_parent(parent: Self) -> Self {
         Self {
-            store: HashMap::new(),
-            parent: Some(parent),
+            parent: Some(Box::new(parent)),
+            ..Self::default()
         }
     }
 
-    pub fn store_binding(&mut self, id: Identifier, val: Val, immutable: bool) {
-        self.store.insert(id, Storeable::Binding(val, immutable));
+    /// Creates the scope of a function body, which falls back to `globals`.
+    pub(crate) fn from_globals(globals: Option<Globals>) -> Self {
+        Self {
+            globals,
+            ..Self::default()
+        }
     }
 
-    pub fn reassign_binding(&mut self, id: Identifier, val: Val) -> Result<(), EvalError> {
-        let Some(previous) = self.store.get(&id) else {
-            return Err(EvalError::IdentifierNotFound(id));
+    /// Creates the scope of a function body, nested in the scopes the function `captured`, the
+    /// outermost of which falls back to `globals`.
+    pub(crate) fn for_call(globals: Option<Globals>, captured: &Captured) -> Self {
+        let mut stores = captured.0.iter();
+        let Some(outermost) = stores.next() else {
+            return Self::from_globals(globals);
+        };
+
+        let root = Self {
+            store: outermost.clone(),
+            globals,
+            ..Self::default()
         };
+        let scope = stores.fold(root, |parent, store| Self {
+            store: store.clone(),
+            ..Self::from_parent(parent)
+        });
 
-        let Storeable::Binding(_, f) = previous else {
-            return Err(EvalError::InvalidType {
-                expected: "binding".into(),
-                received: "function".into(),
-            });
+        Self::from_parent(scope)
+    }
+
+    /// Runs `f` in a fresh child scope of `self`, restoring `self` afterwards regardless of the
+    /// outcome, even if `f` panics.
+    pub fn with_child<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let child = Self::from_parent(std::mem::take(self));
+        let mut scope = ChildScope {
+            parent: self,
+            child,
         };
 
-        if *f {
-            return Err(EvalError::ImmutableReassignment(id));
+        f(&mut scope.child)
+    }
+
+    fn root(&self) -> &Self {
+        match self.parent {
+            Some(ref parent) => parent.root(),
+            None => self,
         }
+    }
 
-        self.store.insert(id, Storeable::Binding(val, *f));
-        Ok(())
+    fn root_mut(&mut self) -> &mut Self {
+        match self.parent {
+            Some(ref mut parent) => parent.root_mut(),
+            None => self,
+        }
     }
 
-    pub fn get_stored_binding(&self, id: &Identifier) -> Result<(Val, bool), EvalError> {
-        match self.store.get(id).cloned() {
-            Some(v) => match v {
-                Storeable::Binding(v, f) => Ok((v, f)),
-                _ => Err(EvalError::InvalidStoredType),
-            },
-            None => match self.parent {
-                Some(v) => v.get_stored_binding(id),
-                None => Err(EvalError::IdentifierNotFound(id.clone())),
+    /// Returns the globals that functions defined in this scope should fall back to. A program
+    /// that wasn't read from a file gets its module context here, so that the functions it
+    /// defines share it with the program rather than creating their own on every call.
+    pub(crate) fn capture(&mut self) -> Globals {
+        let module = self.module_ctx();
+        let root = self.root();
+
+        match root.globals {
+            Some(ref globals) => globals.clone(),
+            None => Globals {
+                store: Arc::downgrade(&root.store),
+                module: Some(module),
             },
         }
     }
 
-    pub fn store_func(&mut self, id: Identifier, params: Vec<Identifier>, body: Expr) {
+    /// Returns the local scopes that functions defined in this scope close over, i.e. every scope
+    /// up the chain but the globals.
+    pub(crate) fn capture_scope(&self) -> Captured {
+        let mut stores = vec![];
+        let mut env = Some(self);
+
+        while let Some(scope) = env {
+            // a root without globals is the top level of a program or module, see `capture`
+            if scope.parent.is_some() || scope.globals.is_some() {
+                stores.push(scope.store.clone());
+            }
+            env = scope.parent.as_deref();
+        }
+
+        stores.reverse();
+        Captured(stores)
+    }
+
+    /// Returns the context of the module this scope belongs to, creating one for a program that
+    /// wasn't read from a file.
+    pub(crate) fn module_ctx(&mut self) -> Arc<ModuleCtx> {
+        let root = self.root_mut();
+
+        if let Some(module) = root.globals.as_ref().and_then(|g| g.module.clone()) {
+            return module;
+        }
+
+        root.module.get_or_insert_default().clone()
+    }
+
+    /// Runs `f` on the closest store that declares `id`, including the globals.
+    fn find_store<T>(
+        &self,
+        id: &Identifier,
+        f: impl FnOnce(&mut Storeable) -> T,
+    ) -> Result<T, EvalError> {
+        if let Some(stored) = self.store.lock().unwrap().get_mut(id) {
+            return Ok(f(stored));
+        }
+
+        if let Some(ref parent) = self.parent {
+            return parent.find_store(id, f);
+        }
+
+        let globals = self.globals.as_ref().and_then(|g| g.store.upgrade());
+        match globals
+            .as_ref()
+            .and_then(|g| g.lock().unwrap().get_mut(id).map(f))
+        {
+            Some(v) => Ok(v),
+            None => Err(EvalError::IdentifierNotFound(id.clone())),
+        }
+    }
+
+    pub fn store_binding(&mut self, id: Identifier, val: Val, immutable: bool) {
+        self.store
+            .lock()
+            .unwrap()
+            .insert(id, Storeable::Binding(val, immutable));
+    }
+
+    /// Reassigns the closest binding named `id`, walking up the parent chain if it isn't declared
+    /// in this scope.
+    pub fn reassign_binding(&mut self, id: Identifier, val: Val) -> Result<(), EvalError> {
+        self.find_store(&id, |previous| {
+            let Storeable::Binding(current, f) = previous else {
+                return Err(EvalError::InvalidType {
+                    expected: "binding".into(),
+                    received: "function".into(),
+                });
+            };
+
+            if *f {
+                return Err(EvalError::ImmutableReassignment(id.clone()));
+            }
+
+            *current = val;
+            Ok(())
+        })?
+    }
+
+    pub fn get_stored(&self, id: &Identifier) -> Result<Storeable, EvalError> {
+        self.find_store(id, |v| v.clone())
+    }
+
+    pub fn get_stored_binding(&self, id: &Identifier) -> Result<(Val, bool), EvalError> {
+        match self.get_stored(id)? {
+            Storeable::Binding(v, f) => Ok((v, f)),
+            _ => Err(EvalError::InvalidStoredType),
+        }
+    }
+
+    pub fn store_func(&mut self, id: Identifier, params: Vec<Param>, body: Expr) {
+        let globals = Some(self.capture());
+        let captured = self.capture_scope();
+        self.store.lock().unwrap().insert(
+            id,
+            Storeable::Func(NamelessFunction {
+                params,
+                body,
+                globals,
+                captured,
+            }),
+        );
+    }
+
+    pub fn store_struct(&mut self, id: Identifier, mut def: StructDef) {
+        let globals = self.capture();
+        for method in def.methods.values_mut() {
+            method.globals = Some(globals.clone());
+        }
+
         self.store
-            .insert(id, Storeable::Func(NamelessFunction { params, body }));
+            .lock()
+            .unwrap()
+            .insert(id, Storeable::Struct(Arc::new(def)));
     }
 
     pub fn get_stored_func(&self, id: &Identifier) -> Result<NamelessFunction, EvalError> {
-        match self.store.get(id).cloned() {
-            Some(v) => match v {
-                Storeable::Func(v) => Ok(v),
-                _ => Err(EvalError::InvalidStoredType),
-            },
-            None => match self.parent {
-                Some(v) => v.get_stored_func(id),
-                None => Err(EvalError::IdentifierNotFound(id.clone())),
-            },
+        match self.get_stored(id)? {
+            Storeable::Func(v) => Ok(v),
+            _ => Err(EvalError::InvalidStoredType),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Env, EvalError, val::Val};
+
+    #[test]
+    fn reassign_parent_binding() {
+        let mut env = Env::new();
+        env.store_binding("x".into(), Val::Real(0.), false);
+
+        env.with_child(|child| child.reassign_binding("x".into(), Val::Real(1.)))
+            .unwrap();
+
+        assert_eq!(
+            env.get_stored_binding(&"x".into()),
+            Ok((Val::Real(1.), false))
+        )
+    }
+
+    #[test]
+    fn reassign_shadowed_binding() {
+        let mut env = Env::new();
+        env.store_binding("x".into(), Val::Real(0.), false);
+
+        env.with_child(|child| {
+            child.store_binding("x".into(), Val::Real(5.), false);
+            child.reassign_binding("x".into(), Val::Real(6.))
+        })
+        .unwrap();
+
+        assert_eq!(
+            env.get_stored_binding(&"x".into()),
+            Ok((Val::Real(0.), false))
+        )
+    }
+
+    #[test]
+    fn do_not_reassign_immutable_parent_binding() {
+        let mut env = Env::new();
+        env.store_binding("x".into(), Val::Real(0.), true);
+
+        assert_eq!(
+            env.with_child(|child| child.reassign_binding("x".into(), Val::Real(1.))),
+            Err(EvalError::ImmutableReassignment("x".into()))
+        )
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt::Display;
+
+use crate::{
+    Env, Eval, EvalError, Parse, ParseError,
+    binding::Identifier,
+    block::Block,
+    expr::Expr,
+    utils::{extract_whitespace, starts_with_keyword, tag},
+    val::Val,
+};
+
+const TRY_KW: &str = "try";
+const CATCH_KW: &str = "catch";
+const FINALLY_KW: &str = "finally";
+pub(crate) const THROW_KW: &str = "throw";
+
+/// A built-in error caught by `catch`, exposing its `kind` (the name of the [`EvalError`]
+/// variant) and a human readable `message`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ErrorVal {
+    pub kind: String,
+    pub message: String,
+}
+
+impl EvalError {
+    /// Returns the name of this error's variant.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::IdentifierNotFound(_) => "IdentifierNotFound",
+            Self::InvalidStoredType => "InvalidStoredType",
+            Self::TooManyArguments { .. } => "TooManyArguments",
+            Self::TooFewArguments { .. } => "TooFewArguments",
+            Self::MissingArgument(_) => "MissingArgument",
+            Self::UnknownArgument(_) => "UnknownArgument",
+            Self::DuplicateArgument(_) => "DuplicateArgument",
+            Self::ImmutableReassignment(_) => "ImmutableReassignment",
+            Self::InvalidType { .. } => "InvalidType",
+            Self::NotIterable(_) => "NotIterable",
+            Self::IndexOutOfBounds { .. } => "IndexOutOfBounds",
+            Self::KeyNotFound(_) => "KeyNotFound",
+            Self::InvalidRangeStep => "InvalidRangeStep",
+            Self::UnknownMember { .. } => "UnknownMember",
+            Self::YieldOutsideGenerator => "YieldOutsideGenerator",
+            Self::IteratorInUse => "IteratorInUse",
+            Self::IteratorPoisoned => "IteratorPoisoned",
+            Self::ModuleNotFound(_) => "ModuleNotFound",
+            Self::ModuleParse { .. } => "ModuleParse",
+            Self::ImportCycle(_) => "ImportCycle",
+            Self::PrivateImport { .. } => "PrivateImport",
+            Self::NestedExport(_) => "NestedExport",
+            Self::Parse(_) => "Parse",
+            Self::MissingField { .. } => "MissingField",
+            Self::InvalidField { .. } => "InvalidField",
+            Self::Thrown(_) => "Thrown",
+            Self::Return(_) => "Return",
+            Self::Break => "Break",
+            Self::Continue => "Continue",
+            Self::BreakOutsideLoop => "BreakOutsideLoop",
+            Self::ContinueOutsideLoop => "ContinueOutsideLoop",
+        }
+    }
+
+    /// Whether `try` may catch this error. Control flow signals pass through it.
+    fn is_catchable(&self) -> bool {
+        !matches!(self, Self::Break | Self::Continue | Self::Return(_))
+    }
+
+    /// Turns a `break` or `continue` that reached the boundary of a function into an error, so
+    /// that it doesn't end or skip a loop of the caller.
+    pub(crate) fn outside_loop(self) -> Self {
+        match self {
+            Self::Break => Self::BreakOutsideLoop,
+            Self::Continue => Self::ContinueOutsideLoop,
+            e => e,
+        }
+    }
+
+    /// Converts this error into the value bound by `catch`. Thrown values are caught as they
+    /// are, while built-in errors become an [`ErrorVal`].
+    fn into_val(self) -> Val {
+        match self {
+            Self::Thrown(v) => v,
+            e => Val::Error(ErrorVal {
+                kind: e.kind().into(),
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IdentifierNotFound(id) => write!(f, "`{}` is not defined", id.0),
+            Self::InvalidStoredType => write!(f, "this name refers to the wrong kind of item"),
+            Self::TooManyArguments { expected, received }
+            | Self::TooFewArguments { expected, received } => {
+                write!(f, "expected {expected} arguments, received {received}")
+            }
+            Self::MissingArgument(id) => write!(f, "missing argument `{}`", id.0),
+            Self::UnknownArgument(id) => write!(f, "unknown argument `{}`", id.0),
+            Self::DuplicateArgument(id) => write!(f, "argument `{}` was passed twice", id.0),
+            Self::ImmutableReassignment(id) => {
+                write!(f, "`{}` is final and can't be reassigned", id.0)
+            }
+            Self::InvalidType { expected, received } => {
+                write!(f, "expected {expected}, received {received}")
+            }
+            Self::NotIterable(ty) => write!(f, "{ty} is not iterable"),
+            Self::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds for length {len}")
+            }
+            Self::KeyNotFound(key) => write!(f, "key \"{key}\" not found"),
+            Self::InvalidRangeStep => write!(f, "invalid range step"),
+            Self::UnknownMember { ty, member } => write!(f, "{ty} has no member `{}`", member.0),
+            Self::YieldOutsideGenerator => write!(f, "`yield` outside of a generator"),
+            Self::IteratorInUse => write!(f, "an iterator was advanced while in use"),
+            Self::IteratorPoisoned => {
+                write!(f, "an iterator was advanced after advancing it panicked")
+            }
+            Self::ModuleNotFound(path) => write!(f, "module `{path}` not found"),
+            Self::ModuleParse { module, error } => {
+                write!(f, "failed to parse module `{module}`: {error:?}")
+            }
+            Self::ImportCycle(chain) => write!(f, "import cycle: {}", chain.join(" -> ")),
+            Self::PrivateImport { module, member } => {
+                write!(f, "`{}` is private to module `{module}`", member.0)
+            }
+            Self::NestedExport(id) => write!(
+                f,
+                "`{}` can't be exported, since `pub` is only allowed at the top level of a module",
+                id.0
+            ),
+            Self::Parse(error) => write!(f, "failed to parse: {error:?}"),
+            Self::MissingField { ty, field } => write!(f, "{ty} is missing field `{field}`"),
+            Self::InvalidField { ty, field, error } => {
+                write!(f, "invalid field `{field}` of {ty}: {error}")
+            }
+            Self::Thrown(v) => write!(f, "uncaught error: {v:?}"),
+            Self::Return(v) => write!(f, "returned early with {v:?}"),
+            Self::Break | Self::BreakOutsideLoop => write!(f, "`break` outside of a loop"),
+            Self::Continue | Self::ContinueOutsideLoop => {
+                write!(f, "`continue` outside of a loop")
+            }
+        }
+    }
+}
+
+/// `throw expr`, which raises `expr` as an error.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Throw {
+    pub expr: Expr,
+}
+
+impl Parse for Throw {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(THROW_KW, &s)?;
+
+        let (s, expr) = Expr::parse(&s)?;
+
+        Ok((s, Self { expr }))
+    }
+}
+
+impl Eval for Throw {
+    fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        Err(EvalError::Thrown(self.expr.eval(env)?))
+    }
+}
+
+/// `try { ... } catch e { ... } finally { ... }`, where at least one of `catch` and `finally` is
+/// required. Evaluates to the value of the `try` block, or of the `catch` block if it caught an
+/// error. The `finally` block always runs last, and only its errors affect the outcome.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TryExpr {
+    pub body: Block,
+    pub catch: Option<(Identifier, Block)>,
+    pub finally: Option<Block>,
+}
+
+impl Parse for TryExpr {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(TRY_KW, &s)?;
+
+        let (s, body) = Block::parse(&s)?;
+
+        let (_, after_ws) = extract_whitespace(&s);
+        let (s, catch) = if starts_with_keyword(CATCH_KW, &after_ws) {
+            let (s, id) = Identifier::parse(&after_ws[CATCH_KW.len()..])?;
+            let (s, block) = Block::parse(&s)?;
+            (s, Some((id, block)))
+        } else {
+            (s, None)
+        };
+
+        let (_, after_ws) = extract_whitespace(&s);
+        let (s, finally) = if starts_with_keyword(FINALLY_KW, &after_ws) {
+            let (s, block) = Block::parse(&after_ws[FINALLY_KW.len()..])?;
+            (s, Some(block))
+        } else {
+            (s, None)
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(ParseError::SequenceNotFound {
+                expected: format!("{CATCH_KW} or {FINALLY_KW}"),
+                received: after_ws,
+            });
+        }
+
+        Ok((
+            s,
+            Self {
+                body,
+                catch,
+                finally,
+            },
+        ))
+    }
+}
+
+impl TryExpr {
+    /// Returns the `try` block, followed by the `catch` and `finally` blocks if there are any.
+    pub(crate) fn blocks(&self) -> impl Iterator<Item = &Block> {
+        [
+            Some(&self.body),
+            self.catch.as_ref().map(|(_, b)| b),
+            self.finally.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl Eval for TryExpr {
+    fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        let out = match (self.body.eval(env), &self.catch) {
+            (Err(e), Some((id, block))) if e.is_catchable() => env.with_child(|env| {
+                env.store_binding(id.clone(), e.into_val(), false);
+                block.eval(env)
+            }),
+            (out, _) => out,
+        };
+
+        if let Some(ref finally) = self.finally {
+            finally.eval(env)?;
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvalError, test_utils::eval_src, val::Val};
+
+    use super::ErrorVal;
+
+    #[test]
+    fn catch_thrown_value() {
+        assert_eq!(
+            eval_src("try { throw \"boom\"; 1 } catch e { [e, 2] }"),
+            Ok(Val::List(vec![Val::Str("boom".into()), Val::Real(2.)]))
+        );
+        assert_eq!(eval_src("try { 1 } catch e { 2 }"), Ok(Val::Real(1.)))
+    }
+
+    #[test]
+    fn catch_builtin_error() {
+        assert_eq!(
+            eval_src("try { x } catch e { e }"),
+            Ok(Val::Error(ErrorVal {
+                kind: "IdentifierNotFound".into(),
+                message: "`x` is not defined".into()
+            }))
+        );
+        assert_eq!(
+            eval_src("try { 1 + \"a\" } catch e { [e.kind, e.message] }"),
+            Ok(Val::List(vec![
+                Val::Str("InvalidType".into()),
+                Val::Str("expected a real number, received String".into())
+            ]))
+        )
+    }
+
+    #[test]
+    fn finally_always_runs() {
+        assert_eq!(
+            eval_src("bind n = 0; bind r = try { 1 } finally { n += 1 }; [r, n]"),
+            Ok(Val::List(vec![Val::Real(1.), Val::Real(1.)]))
+        );
+        assert_eq!(
+            eval_src(
+                "bind n = 0; try { try { throw 1 } finally { n += 1 } } catch e { n += e }; n"
+            ),
+            Ok(Val::Real(2.))
+        )
+    }
+
+    #[test]
+    fn uncaught_and_rethrown() {
+        assert_eq!(
+            eval_src("throw \"boom\""),
+            Err(EvalError::Thrown(Val::Str("boom".into())))
+        );
+        assert_eq!(
+            eval_src("try { throw 1 } catch e { throw e + 1 }"),
+            Err(EvalError::Thrown(Val::Real(2.)))
+        )
+    }
+
+    #[test]
+    fn try_does_not_catch_break() {
+        assert_eq!(
+            eval_src("bind n = 0; for x in 1..10 { try { n += x; break } catch e { n = 100 } }; n"),
+            Ok(Val::Real(1.))
+        )
+    }
+}
@@ -2,9 +2,19 @@ use crate::{
     Eval, EvalError, Parse,
     binding::BindingRef,
     block::Block,
+    coalesce::CoalesceExpr,
+    error::TryExpr,
     fn_call::FuncCall,
-    lit::{LitReal, Literal, Op},
-    utils::tag,
+    func::FuncExpr,
+    index::IndexExpr,
+    list::ListExpr,
+    lit::{Literal, Op},
+    map::MapExpr,
+    member::MemberExpr,
+    membership::InExpr,
+    range::RangeExpr,
+    result::PropagateExpr,
+    utils::{extract_whitespace, tag},
     val::Val,
 };
 
@@ -19,45 +29,21 @@ pub struct MathExpr {
 
 impl Parse for MathExpr {
     fn parse(s: &str) -> crate::ParseOutput<Self> {
-        let (s, lhs) = BindingRef::parse(s)
-            .map(|(s, p)| (s, Expr::binding_ref(p)))
-            .or_else(|_| LitReal::parse(s).map(|(s, p)| (s, Expr::simple(Literal::Real(p)))))?;
+        let (s, lhs) = Expr::parse_operand(s)?;
 
         let (s, op) = Op::parse(&s)?;
-        let (s, rhs) = Expr::parse(&s)?;
+        let (s, rhs) = Expr::parse_arith(&s)?;
 
-        Ok((s.into(), Self { lhs, op, rhs }))
+        Ok((s, Self { lhs, op, rhs }))
     }
 }
 
 impl Eval for MathExpr {
     fn eval(&self, env: &mut crate::env::Env) -> Result<crate::val::Val, crate::EvalError> {
-        let lhs = match self.lhs.eval(env)? {
-            Val::Real(lhs) => lhs,
-            v => {
-                return Err(EvalError::InvalidType {
-                    expected: "a real number".into(),
-                    received: v.get_type().into(),
-                });
-            }
-        };
-
-        let rhs = match self.rhs.eval(env)? {
-            Val::Real(lhs) => lhs,
-            v => {
-                return Err(EvalError::InvalidType {
-                    expected: "a real number".into(),
-                    received: v.get_type().into(),
-                });
-            }
-        };
+        let lhs = self.lhs.eval(env)?;
+        let rhs = self.rhs.eval(env)?;
 
-        Ok(crate::val::Val::Real(match self.op {
-            Op::Add => lhs + rhs,
-            Op::Sub => lhs - rhs,
-            Op::Mul => lhs * rhs,
-            Op::Div => lhs / rhs,
-        }))
+        self.op.apply(lhs, rhs)
     }
 }
 
@@ -68,14 +54,28 @@ enum InnerExpr {
     BindingRef(BindingRef),
     FuncCall(FuncCall),
     Block(Block),
+    List(ListExpr),
+    Map(MapExpr),
+    Index(Box<IndexExpr>),
+    Range(Box<RangeExpr>),
+    In(Box<InExpr>),
+    Member(Box<MemberExpr>),
+    Func(Box<FuncExpr>),
+    Try(Box<TryExpr>),
+    Propagate(Box<PropagateExpr>),
+    Coalesce(Box<CoalesceExpr>),
 }
 
-impl Parse for InnerExpr {
-    fn parse(s: &str) -> crate::ParseOutput<Self> {
-        FuncCall::parse(s)
-            .map(|(s, p)| (s, Self::FuncCall(p)))
-            .or_else(|_| MathExpr::parse(s).map(|(s, p)| (s, Self::MathExpr(p.into()))))
+impl InnerExpr {
+    /// Parses an expression that can't be split any further, i.e. anything but operators.
+    fn parse_primary(s: &str) -> crate::ParseOutput<Self> {
+        FuncExpr::parse(s)
+            .map(|(s, p)| (s, Self::Func(p.into())))
+            .or_else(|_| TryExpr::parse(s).map(|(s, p)| (s, Self::Try(p.into()))))
+            .or_else(|_| FuncCall::parse(s).map(|(s, p)| (s, Self::FuncCall(p))))
             .or_else(|_| Literal::parse(s).map(|(s, p)| (s, Self::Simple(p))))
+            .or_else(|_| MapExpr::parse(s).map(|(s, p)| (s, Self::Map(p))))
+            .or_else(|_| ListExpr::parse(s).map(|(s, p)| (s, Self::List(p))))
             .or_else(|_| BindingRef::parse(s).map(|(s, p)| (s, Self::BindingRef(p))))
             .or_else(|_| Block::parse(s).map(|(s, p)| (s, Self::Block(p))))
     }
@@ -89,6 +89,16 @@ impl Eval for InnerExpr {
             Self::BindingRef(b_ref) => b_ref.eval(env),
             Self::Block(block) => block.eval(env),
             Self::FuncCall(fnc) => fnc.eval(env),
+            Self::List(list) => list.eval(env),
+            Self::Map(map) => map.eval(env),
+            Self::Index(index) => index.eval(env),
+            Self::Range(range) => range.eval(env),
+            Self::In(in_expr) => in_expr.eval(env),
+            Self::Member(member) => member.eval(env),
+            Self::Func(func) => func.eval(env),
+            Self::Try(try_expr) => try_expr.eval(env),
+            Self::Propagate(expr) => expr.eval(env),
+            Self::Coalesce(expr) => expr.eval(env),
         }
     }
 }
@@ -135,27 +145,154 @@ impl Expr {
         }
     }
 
+    pub fn list(list: ListExpr) -> Self {
+        Self {
+            negate: None,
+            inner: InnerExpr::List(list),
+        }
+    }
+
+    pub fn index(index: IndexExpr) -> Self {
+        Self {
+            negate: None,
+            inner: InnerExpr::Index(index.into()),
+        }
+    }
+
+    pub fn range(range: RangeExpr) -> Self {
+        Self {
+            negate: None,
+            inner: InnerExpr::Range(range.into()),
+        }
+    }
+
+    pub fn in_expr(in_expr: InExpr) -> Self {
+        Self {
+            negate: None,
+            inner: InnerExpr::In(in_expr.into()),
+        }
+    }
+
+    pub fn member(member: MemberExpr) -> Self {
+        Self {
+            negate: None,
+            inner: InnerExpr::Member(member.into()),
+        }
+    }
+
+    /// Returns the block `self` consists of, if it's nothing but a block.
+    pub(crate) fn as_block(&self) -> Option<&Block> {
+        match (&self.negate, &self.inner) {
+            (None, InnerExpr::Block(block)) => Some(block),
+            _ => None,
+        }
+    }
+
+    /// Returns the member access `self` consists of, if it's nothing but one.
+    pub(crate) fn as_member(&self) -> Option<&MemberExpr> {
+        match (&self.negate, &self.inner) {
+            (None, InnerExpr::Member(member)) => Some(member),
+            _ => None,
+        }
+    }
+
+    /// Returns the `try` expression `self` consists of, if it's nothing but one.
+    pub(crate) fn as_try(&self) -> Option<&TryExpr> {
+        match (&self.negate, &self.inner) {
+            (None, InnerExpr::Try(try_expr)) => Some(try_expr),
+            _ => None,
+        }
+    }
+
     pub fn negate(&mut self) {
         self.negate = Some(Negate);
     }
+
+    /// Parses an optionally negated primary expression, followed by any number of indexing
+    /// operations, member accesses and `?`, e.g. `!xs[0].done()?`.
+    pub(crate) fn parse_operand(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let (s, negate) = Negate::parse(&s).unwrap();
+        let (s, inner) = InnerExpr::parse_primary(&s)?;
+
+        let mut expr = Self {
+            negate: None,
+            inner,
+        };
+        let mut s = s;
+
+        loop {
+            if let Ok((new_s, index)) = IndexExpr::parse_subscript(&s) {
+                expr = Self::index(IndexExpr {
+                    target: expr,
+                    index,
+                });
+                s = new_s;
+            } else if let Ok((new_s, (member, args, optional))) = MemberExpr::parse_member(&s) {
+                expr = Self::member(MemberExpr {
+                    target: expr,
+                    member,
+                    args,
+                    optional,
+                });
+                s = new_s;
+            } else if let Ok(new_s) = PropagateExpr::parse_token(&s) {
+                expr = Self {
+                    negate: None,
+                    inner: InnerExpr::Propagate(PropagateExpr { expr }.into()),
+                };
+                s = new_s.into();
+            } else {
+                break;
+            }
+        }
+
+        expr.negate = negate;
+        Ok((s, expr))
+    }
+
+    /// Parses arithmetic, which binds tighter than ranges and membership tests.
+    pub(crate) fn parse_arith(s: &str) -> crate::ParseOutput<Self> {
+        MathExpr::parse(s)
+            .map(|(s, p)| (s, Self::math_expr(p.into())))
+            .or_else(|_| Self::parse_operand(s))
+    }
 }
 
 impl Parse for Expr {
     fn parse(s: &str) -> crate::ParseOutput<Self> {
-        let (s, negate) = Negate::parse(s).unwrap();
-        let (s, inner) = InnerExpr::parse(&s)?;
+        let (s, expr) = RangeExpr::parse_or_operand(s)?;
+
+        let (s, expr) = match InExpr::parse_rhs(&s) {
+            Ok((s, rhs)) => (s, Self::in_expr(InExpr { lhs: expr, rhs })),
+            Err(_) => (s, expr),
+        };
 
-        Ok((s, Self { inner, negate }))
+        match CoalesceExpr::parse_rhs(&s) {
+            Ok((s, rhs)) => Ok((
+                s,
+                Self {
+                    negate: None,
+                    inner: InnerExpr::Coalesce(CoalesceExpr { lhs: expr, rhs }.into()),
+                },
+            )),
+            Err(_) => Ok((s, expr)),
+        }
     }
 }
 
 impl Eval for Expr {
     fn eval(&self, env: &mut crate::Env) -> Result<Val, EvalError> {
         let expr_val = self.inner.eval(env)?;
-        Ok(match expr_val {
-            Val::Bool(b) => Val::Bool(!b),
-            v => v,
-        })
+
+        match (self.negate, expr_val) {
+            (None, v) => Ok(v),
+            (Some(_), Val::Bool(b)) => Ok(Val::Bool(!b)),
+            (Some(_), v) => Err(EvalError::InvalidType {
+                expected: "a boolean".into(),
+                received: v.get_type().into(),
+            }),
+        }
     }
 }
 
@@ -178,7 +315,7 @@ mod tests {
         binding::{Binding, BindingRef},
         env::Env,
         expr::{Expr, MathExpr},
-        fn_call::FuncCall,
+        fn_call::{Arg, FuncCall},
         lit::{LitReal, LitStr, Op},
         val::Val,
     };
@@ -297,8 +434,8 @@ mod tests {
                 Expr::func_call(FuncCall {
                     callee: "test".into(),
                     params: vec![
-                        Expr::binding_ref(BindingRef { id: "hello".into() }),
-                        Expr::binding_ref(BindingRef { id: "world".into() })
+                        Arg::positional(Expr::binding_ref(BindingRef { id: "hello".into() })),
+                        Arg::positional(Expr::binding_ref(BindingRef { id: "world".into() }))
                     ]
                 })
             ))
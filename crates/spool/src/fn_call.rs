@@ -1,58 +1,288 @@
 use crate::{
-    Env, Eval, Parse,
+    Env, Eval, EvalError, Parse,
     binding::Identifier,
+    builtins,
+    env::{NamelessFunction, Storeable},
     expr::Expr,
-    utils::{extract_whitespace, tag},
+    func::{Param, SPREAD_TOKEN},
+    generator::Generator,
+    iter::{Iter, IterRef},
+    structs::StructDef,
+    utils::{extract_inline_whitespace, extract_whitespace, tag},
+    val::Val,
 };
 
+const NAMED_ARG_SEPARATOR: &str = ":";
+
+/// An argument at a call site, either positional or named, as in `greet(name: "Ann")`. A spread
+/// argument (`...args`) passes every element of a list as a separate positional argument.
 #[derive(Debug, PartialEq, Clone)]
-pub struct FuncCall {
-    pub(crate) callee: Identifier,
-    pub(crate) params: Vec<Expr>,
+pub struct Arg {
+    pub(crate) name: Option<Identifier>,
+    pub(crate) expr: Expr,
+    pub(crate) spread: bool,
 }
 
-impl Parse for FuncCall {
-    fn parse(s: &str) -> crate::ParseOutput<Self> {
+impl Arg {
+    pub fn positional(expr: Expr) -> Self {
+        Self {
+            name: None,
+            expr,
+            spread: false,
+        }
+    }
+
+    fn parse_spread(s: &str) -> crate::ParseOutput<Self> {
         let (_, s) = extract_whitespace(s);
-        let (s, id) = Identifier::parse(&s)?;
+        let s = tag(SPREAD_TOKEN, &s)?;
+        let (s, expr) = Expr::parse(&s)?;
 
-        let s = tag("(", &s)?;
+        Ok((
+            s,
+            Self {
+                name: None,
+                expr,
+                spread: true,
+            },
+        ))
+    }
+
+    fn parse_named(s: &str) -> crate::ParseOutput<Self> {
+        let (s, name) = Identifier::parse(s)?;
+        let (_, s) = extract_inline_whitespace(&s);
+        let s = tag(NAMED_ARG_SEPARATOR, &s)?;
+        let (s, expr) = Expr::parse(&s)?;
+
+        Ok((
+            s,
+            Self {
+                name: Some(name),
+                expr,
+                spread: false,
+            },
+        ))
+    }
+}
 
-        let mut params = vec![];
-        let mut s = s;
+impl Parse for Arg {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        Self::parse_spread(s)
+            .or_else(|_| Self::parse_named(s))
+            .or_else(|_| Expr::parse(s).map(|(s, p)| (s, Self::positional(p))))
+    }
+}
 
-        while let Ok((new_s, id)) = Expr::parse(&s) {
-            params.push(id);
-            s = match tag(",", &new_s) {
-                Ok(v) => v,
-                Err(_) => new_s,
-            };
+/// The evaluated arguments of a call, with spread arguments already expanded.
+#[derive(Debug, Default)]
+pub(crate) struct CallArgs {
+    pub(crate) positional: Vec<Val>,
+    pub(crate) named: Vec<(Identifier, Val)>,
+}
+
+impl CallArgs {
+    pub(crate) fn positional(positional: Vec<Val>) -> Self {
+        Self {
+            positional,
+            named: vec![],
         }
+    }
 
-        let s = tag(")", &s)?;
+    pub(crate) fn eval(args: &[Arg], env: &mut Env) -> Result<Self, EvalError> {
+        let mut out = Self::default();
 
-        Ok((s, Self { callee: id, params }))
+        for arg in args {
+            let val = arg.expr.eval(env)?;
+
+            match (&arg.name, arg.spread) {
+                (Some(name), _) => out.named.push((name.clone(), val)),
+                (None, true) => match val {
+                    Val::List(items) => out.positional.extend(items),
+                    v => {
+                        return Err(EvalError::InvalidType {
+                            expected: "a list to spread".into(),
+                            received: v.get_type().into(),
+                        });
+                    }
+                },
+                (None, false) => out.positional.push(val),
+            }
+        }
+
+        Ok(out)
     }
 }
 
-impl Eval for FuncCall {
-    fn eval(&self, env: &mut crate::Env) -> Result<crate::val::Val, crate::EvalError> {
-        let fn_id = &self.callee;
-        let fn_def = env.get_stored_func(fn_id)?;
-        let fn_params = fn_def.params;
-        let call_params = &self.params;
+/// Parses a parenthesized, comma separated argument list.
+pub(crate) fn parse_args(s: &str) -> crate::ParseOutput<Vec<Arg>> {
+    let s = tag("(", s)?;
+
+    let mut args = vec![];
+    let mut s = s;
+
+    while let Ok((new_s, arg)) = Arg::parse(&s) {
+        args.push(arg);
+        s = match tag(",", &extract_whitespace(&new_s).1) {
+            Ok(v) => v,
+            Err(_) => new_s,
+        };
+    }
+
+    let (_, s) = extract_whitespace(&s);
+    let s = tag(")", &s)?;
+
+    Ok((s, args))
+}
 
-        if fn_params.len() != call_params.len() {
-            return Err(crate::EvalError::InvalidArgumentLen);
+/// Binds `args` to `params` in `env`, which should be a fresh scope. Parameters are filled by
+/// positional arguments first and then by name, and any that remain fall back to their defaults.
+pub(crate) fn bind_params(
+    params: &[Param],
+    args: CallArgs,
+    mut env: Env,
+) -> Result<Env, EvalError> {
+    let mut values: Vec<Option<Val>> = vec![None; params.len()];
+    let rest_idx = params.iter().position(|p| p.variadic);
+    let fixed_len = rest_idx.unwrap_or(params.len());
+    let received = args.positional.len();
+
+    let mut positional = args.positional.into_iter();
+    for (slot, val) in values[..fixed_len].iter_mut().zip(positional.by_ref()) {
+        *slot = Some(val);
+    }
+
+    let extra: Vec<Val> = positional.collect();
+    match rest_idx {
+        Some(idx) if !extra.is_empty() => values[idx] = Some(Val::List(extra)),
+        None if !extra.is_empty() => {
+            return Err(EvalError::TooManyArguments {
+                expected: fixed_len,
+                received,
+            });
         }
+        _ => (),
+    }
 
-        let mut fn_env = Env::new();
+    for (name, val) in args.named {
+        let idx = params
+            .iter()
+            .position(|p| p.id == name)
+            .ok_or_else(|| EvalError::UnknownArgument(name.clone()))?;
 
-        for (idx, call_param) in call_params.iter().enumerate() {
-            fn_env.store_binding(fn_params[idx].clone(), call_param.eval(env)?, false); // possibly reconsider this
+        if values[idx].is_some() {
+            return Err(EvalError::DuplicateArgument(name));
         }
 
-        fn_def.body.eval(&mut fn_env)
+        values[idx] = Some(val);
+    }
+
+    for (param, val) in params.iter().zip(values) {
+        let val = match (val, &param.default) {
+            (Some(val), _) => val,
+            (None, Some(default)) => default.eval(&mut env)?,
+            (None, None) if param.variadic => Val::List(vec![]),
+            (None, None) => return Err(EvalError::MissingArgument(param.id.clone())),
+        };
+
+        env.store_binding(param.id.clone(), val, false); // possibly reconsider this
+    }
+
+    Ok(env)
+}
+
+/// Binds `args` to parameters called `names`, so that built-in methods accept named arguments
+/// and report missing ones just like user-defined functions.
+pub(crate) fn expect_args<const N: usize>(
+    args: CallArgs,
+    names: [&str; N],
+) -> Result<[Val; N], EvalError> {
+    let params: Vec<Param> = names
+        .iter()
+        .map(|name| Param {
+            id: Identifier((*name).into()),
+            default: None,
+            variadic: false,
+        })
+        .collect();
+
+    let env = bind_params(&params, args, Env::new())?;
+
+    Ok(names.map(|name| {
+        env.get_stored_binding(&Identifier(name.into()))
+            .expect("all parameters are bound")
+            .0
+    }))
+}
+
+impl NamelessFunction {
+    pub(crate) fn call(&self, args: CallArgs) -> Result<Val, EvalError> {
+        let mut fn_env = bind_params(
+            &self.params,
+            args,
+            Env::for_call(self.globals.clone(), &self.captured),
+        )?;
+
+        // calling a generator only sets it up, its body runs as it's iterated
+        if let Some(body) = self.body.as_block().filter(|b| b.contains_yield()) {
+            let generator = Generator::new(body, fn_env);
+            return Ok(Val::Iter(IterRef::new(Iter::Gen(generator.into()))));
+        }
+
+        match self.body.eval(&mut fn_env) {
+            Err(EvalError::Return(v)) => Ok(v),
+            out => out.map_err(EvalError::outside_loop),
+        }
+    }
+}
+
+impl Storeable {
+    /// Calls a stored function, constructs a struct, or calls a function value.
+    pub(crate) fn call(self, args: CallArgs) -> Result<Val, EvalError> {
+        match self {
+            Self::Func(f) => f.call(args),
+            Self::Struct(def) => StructDef::construct(&def, args),
+            Self::Native(f) => f.call(args),
+            Self::Binding(v, _) => call_val(&v, args),
+        }
+    }
+}
+
+/// Calls `callee`, which has to be a function value.
+pub(crate) fn call_val(callee: &Val, args: CallArgs) -> Result<Val, EvalError> {
+    match callee {
+        Val::Func(f) => f.call(args),
+        v => Err(EvalError::InvalidType {
+            expected: "a function".into(),
+            received: v.get_type().into(),
+        }),
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FuncCall {
+    pub(crate) callee: Identifier,
+    pub(crate) params: Vec<Arg>,
+}
+
+impl Parse for FuncCall {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let (s, id) = Identifier::parse(&s)?;
+        let (s, params) = parse_args(&s)?;
+
+        Ok((s, Self { callee: id, params }))
+    }
+}
+
+impl Eval for FuncCall {
+    fn eval(&self, env: &mut crate::Env) -> Result<crate::val::Val, crate::EvalError> {
+        let callee = match env.get_stored(&self.callee) {
+            Err(EvalError::IdentifierNotFound(id)) => match builtins::lookup(&id, env) {
+                Some(builtin) => return builtin(CallArgs::eval(&self.params, env)?),
+                None => return Err(EvalError::IdentifierNotFound(id)),
+            },
+            callee => callee?,
+        };
+        callee.call(CallArgs::eval(&self.params, env)?)
     }
 }
 
@@ -63,9 +293,10 @@ mod tests {
         binding::BindingRef,
         block::Block,
         expr::{Expr, MathExpr},
-        fn_call::FuncCall,
+        fn_call::{Arg, FuncCall},
         lit::{LitReal, Literal},
         stmt::Stmt,
+        test_utils::eval_src,
         val::Val,
     };
 
@@ -91,7 +322,9 @@ mod tests {
                 "".into(),
                 FuncCall {
                     callee: "test".into(),
-                    params: vec![Expr::simple(Literal::Real(crate::lit::LitReal(5.)))]
+                    params: vec![Arg::positional(Expr::simple(Literal::Real(
+                        crate::lit::LitReal(5.)
+                    )))]
                 }
             ))
         )
@@ -106,8 +339,8 @@ mod tests {
                 FuncCall {
                     callee: "test".into(),
                     params: vec![
-                        Expr::binding_ref(BindingRef { id: "hello".into() }),
-                        Expr::binding_ref(BindingRef { id: "world".into() })
+                        Arg::positional(Expr::binding_ref(BindingRef { id: "hello".into() })),
+                        Arg::positional(Expr::binding_ref(BindingRef { id: "world".into() }))
                     ]
                 }
             ))
@@ -134,8 +367,8 @@ mod tests {
                     Stmt::Expr(crate::expr::Expr::func_call(FuncCall {
                         callee: "testfn".into(),
                         params: vec![
-                            Expr::simple(Literal::Real(LitReal(5.))),
-                            Expr::simple(Literal::Real(LitReal(5.)))
+                            Arg::positional(Expr::simple(Literal::Real(LitReal(5.)))),
+                            Arg::positional(Expr::simple(Literal::Real(LitReal(5.))))
                         ]
                     }))
                 ]
@@ -144,4 +377,104 @@ mod tests {
             Ok(Val::Real(10.))
         )
     }
+
+    #[test]
+    fn parse_named_arg() {
+        assert_eq!(
+            FuncCall::parse("greet(name: \"Ann\")"),
+            Ok((
+                "".into(),
+                FuncCall {
+                    callee: "greet".into(),
+                    params: vec![Arg {
+                        name: Some("name".into()),
+                        expr: Expr::simple(Literal::Str("Ann".into())),
+                        spread: false
+                    }]
+                }
+            ))
+        )
+    }
+
+    #[test]
+    fn eval_default_and_named_args() {
+        assert_eq!(
+            eval_src("func f(a, b = a * 2, c = 1) => a + b + c; f(1, c: 10)"),
+            Ok(Val::Real(13.))
+        );
+        assert_eq!(
+            eval_src("func f(a, b = 5) => a - b; f(b: 1, a: 3)"),
+            Ok(Val::Real(2.))
+        )
+    }
+
+    #[test]
+    fn eval_missing_arg() {
+        assert_eq!(
+            eval_src("func f(a, b = 5) => a; f(b: 1)"),
+            Err(crate::EvalError::MissingArgument("a".into()))
+        )
+    }
+
+    #[test]
+    fn eval_unknown_arg() {
+        assert_eq!(
+            eval_src("func f(a) => a; f(1, c: 2)"),
+            Err(crate::EvalError::UnknownArgument("c".into()))
+        )
+    }
+
+    #[test]
+    fn eval_duplicate_arg() {
+        assert_eq!(
+            eval_src("func f(a) => a; f(1, a: 2)"),
+            Err(crate::EvalError::DuplicateArgument("a".into()))
+        )
+    }
+
+    #[test]
+    fn eval_too_many_args() {
+        assert_eq!(
+            eval_src("func f(a) => a; f(1, 2)"),
+            Err(crate::EvalError::TooManyArguments {
+                expected: 1,
+                received: 2
+            })
+        )
+    }
+
+    #[test]
+    fn eval_rest_param() {
+        assert_eq!(
+            eval_src("func f(first, ...rest) => rest; f(1, 2, 3)"),
+            Ok(Val::List(vec![Val::Real(2.), Val::Real(3.)]))
+        );
+        assert_eq!(
+            eval_src("func f(first, ...rest) => rest; f(1)"),
+            Ok(Val::List(vec![]))
+        )
+    }
+
+    #[test]
+    fn eval_spread_arg() {
+        assert_eq!(
+            eval_src("func f(a, b, c) => a + b * c; bind args = [10, 4]; f(...args, 2)"),
+            Ok(Val::Real(18.))
+        );
+        assert_eq!(
+            eval_src("func f(...xs) => xs; f(0, ...[1, 2])"),
+            Ok(Val::List(vec![Val::Real(0.), Val::Real(1.), Val::Real(2.)]))
+        )
+    }
+
+    #[test]
+    fn eval_spread_non_list() {
+        assert_eq!(
+            eval_src("func f(a) => a; f(...5)"),
+            Err(crate::EvalError::InvalidType {
+                expected: "a list to spread".into(),
+                received: "Real number".into()
+            })
+        )
+    }
 }
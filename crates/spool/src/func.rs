@@ -1,46 +1,153 @@
 use crate::{
-    Eval, Parse,
-    binding::Identifier,
+    Eval, Parse, ParseError,
+    binding::{ASSIGN_TOKEN, Identifier},
+    block::Block,
+    env::NamelessFunction,
     expr::Expr,
+    generator::YIELD_KW,
     utils::{extract_whitespace, tag},
     val::Val,
 };
 
-const FUNC_KW: &str = "func";
+pub(crate) const FUNC_KW: &str = "func";
+pub(crate) const SPREAD_TOKEN: &str = "...";
 
+/// A function parameter, optionally with a default value such as `greeting = "hi"`.
+///
+/// Defaults are evaluated in the function's environment at call time, so they may refer to the
+/// parameters declared before them. A trailing rest parameter (`...xs`) collects any remaining
+/// positional arguments into a list.
 #[derive(Debug, Clone, PartialEq)]
-pub struct FuncDef {
+pub struct Param {
     pub(crate) id: Identifier,
-    pub(crate) params: Vec<Identifier>,
-    pub(crate) body: Expr,
+    pub(crate) default: Option<Expr>,
+    pub(crate) variadic: bool,
 }
 
-impl Parse for FuncDef {
+impl Parse for Param {
     fn parse(s: &str) -> crate::ParseOutput<Self> {
         let (_, s) = extract_whitespace(s);
-        let s = tag(FUNC_KW, &s)?;
+        if let Ok(s) = tag(SPREAD_TOKEN, &s) {
+            let (s, id) = Identifier::parse(&s)?;
+
+            return Ok((
+                s,
+                Self {
+                    id,
+                    default: None,
+                    variadic: true,
+                },
+            ));
+        }
 
-        let (_, s) = extract_whitespace(&s);
         let (s, id) = Identifier::parse(&s)?;
 
-        let s = tag("(", &s)?;
+        let (_, after_ws) = extract_whitespace(&s);
+        let Ok(s) = tag(ASSIGN_TOKEN, &after_ws) else {
+            return Ok((
+                s,
+                Self {
+                    id,
+                    default: None,
+                    variadic: false,
+                },
+            ));
+        };
 
-        let mut params = vec![];
-        let mut s = s;
+        let (s, default) = Expr::parse(&s)?;
 
-        while let Ok((new_s, id)) = Identifier::parse(&s) {
-            params.push(id);
-            s = match tag(",", &new_s) {
-                Ok(v) => v,
-                Err(_) => new_s,
-            };
+        Ok((
+            s,
+            Self {
+                id,
+                default: Some(default),
+                variadic: false,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+impl From<&'_ str> for Param {
+    fn from(value: &'_ str) -> Self {
+        Self {
+            id: value.into(),
+            default: None,
+            variadic: false,
         }
+    }
+}
 
-        let s = tag(")", &s)?;
-        let (_, s) = extract_whitespace(&s);
-        let s = tag("=>", &s)?;
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncDef {
+    pub(crate) id: Identifier,
+    pub(crate) params: Vec<Param>,
+    pub(crate) body: Expr,
+}
+
+/// Parses the `(params) => body` part shared by named and anonymous functions.
+fn parse_signature(s: &str) -> crate::ParseOutput<(Vec<Param>, Expr)> {
+    let s = tag("(", s)?;
+
+    let mut params = vec![];
+    let mut s = s;
+
+    while let Ok((new_s, param)) = Param::parse(&s) {
+        params.push(param);
+        s = match tag(",", &extract_whitespace(&new_s).1) {
+            Ok(v) => v,
+            Err(_) => new_s,
+        };
+    }
+
+    if let Some(rest) = params[..params.len().saturating_sub(1)]
+        .iter()
+        .find(|p| p.variadic)
+    {
+        return Err(ParseError::InvalidSequence {
+            expected: "the rest parameter to be the last parameter".into(),
+            received: rest.id.0.clone(),
+        });
+    }
+
+    // otherwise the later parameter would silently shadow the earlier one
+    if let Some(dup) = params
+        .iter()
+        .enumerate()
+        .find_map(|(idx, p)| params[..idx].iter().any(|q| q.id == p.id).then_some(p))
+    {
+        return Err(ParseError::InvalidSequence {
+            expected: "unique parameter names".into(),
+            received: dup.id.0.clone(),
+        });
+    }
 
-        let (s, body) = Expr::parse(&s)?;
+    let s = tag(")", &extract_whitespace(&s).1)?;
+    let (_, s) = extract_whitespace(&s);
+    let s = tag("=>", &s)?;
+
+    let (s, body) = Expr::parse(&s)?;
+
+    if body.as_block().is_some_and(Block::yields_in_try) {
+        return Err(ParseError::InvalidSequence {
+            expected: format!(
+                "no `{YIELD_KW}` inside `try`, since generators can't be suspended there"
+            ),
+            received: YIELD_KW.into(),
+        });
+    }
+
+    Ok((s, (params, body)))
+}
+
+impl Parse for FuncDef {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(FUNC_KW, &s)?;
+
+        let (_, s) = extract_whitespace(&s);
+        let (s, id) = Identifier::parse(&s)?;
+        let (s, (params, body)) = parse_signature(&s)?;
 
         Ok((s, Self { id, params, body }))
     }
@@ -54,9 +161,47 @@ impl Eval for FuncDef {
     }
 }
 
+/// An anonymous function such as `func(x) => x * 2`, which evaluates to a function value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncExpr {
+    pub(crate) params: Vec<Param>,
+    pub(crate) body: Expr,
+}
+
+impl Parse for FuncExpr {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(FUNC_KW, &s)?;
+
+        let (_, s) = extract_whitespace(&s);
+        let (s, (params, body)) = parse_signature(&s)?;
+
+        Ok((s, Self { params, body }))
+    }
+}
+
+impl Eval for FuncExpr {
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, crate::EvalError> {
+        Ok(Val::Func(
+            NamelessFunction {
+                params: self.params.clone(),
+                body: self.body.clone(),
+                globals: Some(env.capture()),
+                captured: env.capture_scope(),
+            }
+            .into(),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Env, Eval, Parse, func::FuncDef, val::Val};
+    use crate::{
+        Env, Eval, Parse,
+        func::{FuncDef, Param},
+        test_utils::{eval_src, reals},
+        val::Val,
+    };
 
     #[test]
     fn parse() {
@@ -87,4 +232,118 @@ mod tests {
             Ok(Val::Unit)
         )
     }
+
+    #[test]
+    fn parse_default_param() {
+        assert_eq!(
+            FuncDef::parse("func greet(name, greeting = \"hi\") => greeting"),
+            Ok((
+                "".into(),
+                FuncDef {
+                    id: "greet".into(),
+                    params: vec![
+                        "name".into(),
+                        Param {
+                            id: "greeting".into(),
+                            default: Some(crate::expr::Expr::simple(crate::lit::Literal::Str(
+                                "hi".into()
+                            ))),
+                            variadic: false
+                        }
+                    ],
+                    body: crate::expr::Expr::binding_ref(crate::binding::BindingRef {
+                        id: "greeting".into()
+                    })
+                }
+            ))
+        )
+    }
+
+    #[test]
+    fn parse_rest_param() {
+        assert_eq!(
+            FuncDef::parse("func sum(first, ...rest) => first").map(|(_, f)| f.params),
+            Ok(vec![
+                "first".into(),
+                Param {
+                    id: "rest".into(),
+                    default: None,
+                    variadic: true
+                }
+            ])
+        )
+    }
+
+    #[test]
+    fn parse_duplicate_param() {
+        assert_eq!(
+            FuncDef::parse("func f(a, b, a) => a"),
+            Err(crate::ParseError::InvalidSequence {
+                expected: "unique parameter names".into(),
+                received: "a".into()
+            })
+        )
+    }
+
+    #[test]
+    fn parse_rest_param_not_last() {
+        assert_eq!(
+            FuncDef::parse("func f(...rest, last) => last"),
+            Err(crate::ParseError::InvalidSequence {
+                expected: "the rest parameter to be the last parameter".into(),
+                received: "rest".into()
+            })
+        )
+    }
+
+    #[test]
+    fn eval_func_expr() {
+        assert_eq!(
+            eval_src("bind double = func(x) => x * 2; double(4)"),
+            Ok(Val::Real(8.))
+        )
+    }
+
+    #[test]
+    fn func_as_value() {
+        assert_eq!(
+            eval_src("func double(x) => x * 2; bind f = double; f(4)"),
+            Ok(Val::Real(8.))
+        )
+    }
+
+    #[test]
+    fn func_expr_captures_locals() {
+        assert_eq!(
+            eval_src(
+                "func outer() => { bind k = 10; [1, 2].map(func(x) => x + k).collect() }; outer()"
+            ),
+            Ok(reals(&[11., 12.]))
+        );
+        assert_eq!(
+            eval_src("func make(n) => func(x) => x + n; bind add = make(1); add(2)"),
+            Ok(Val::Real(3.))
+        );
+        assert_eq!(
+            eval_src(
+                "func counter() => { bind n = 0; bind tick = func() => { n = n + 1; n }; tick }; \
+                 bind next = counter(); next(); next()"
+            ),
+            Ok(Val::Real(2.))
+        )
+    }
+
+    #[test]
+    fn named_func_captures_locals() {
+        assert_eq!(
+            eval_src("{ bind y = 3; func f() => y; f() }"),
+            Ok(Val::Real(3.))
+        );
+        assert_eq!(
+            eval_src(
+                "func outer() => { bind k = 2; func scale(x) => x * k; k = 4; scale(3) }; outer()"
+            ),
+            Ok(Val::Real(12.))
+        )
+    }
 }
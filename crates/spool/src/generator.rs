@@ -0,0 +1,304 @@
+use crate::{
+    Env, Eval, EvalError, Parse,
+    binding::Identifier,
+    block::Block,
+    expr::Expr,
+    iter::Iter,
+    stmt::Stmt,
+    utils::{extract_whitespace, tag},
+    val::Val,
+};
+
+pub(crate) const YIELD_KW: &str = "yield";
+
+/// `yield expr`, which suspends the enclosing generator and hands `expr` to whoever called
+/// `next()` on it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Yield {
+    pub expr: Expr,
+}
+
+impl Parse for Yield {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(YIELD_KW, &s)?;
+
+        let (s, expr) = Expr::parse(&s)?;
+
+        Ok((s, Self { expr }))
+    }
+}
+
+impl Eval for Yield {
+    /// Reached only when a `yield` isn't run by a [`Generator`], e.g. at the top level or nested
+    /// inside an expression.
+    fn eval(&self, _env: &mut Env) -> Result<Val, EvalError> {
+        Err(EvalError::YieldOutsideGenerator)
+    }
+}
+
+impl Block {
+    /// Returns whether any statement of this block, or of a block or loop nested in it, yields.
+    /// Functions whose body contains a `yield` are generators.
+    pub(crate) fn contains_yield(&self) -> bool {
+        self.stmts.iter().any(|stmt| match stmt {
+            Stmt::Yield(_) => true,
+            Stmt::For(f) => f.body.contains_yield(),
+            Stmt::Expr(e) => e.as_block().is_some_and(Self::contains_yield),
+            _ => false,
+        })
+    }
+
+    /// Returns whether a `yield` of this block is inside a `try` statement, where a generator
+    /// can't be suspended.
+    pub(crate) fn yields_in_try(&self) -> bool {
+        self.stmts.iter().any(|stmt| match stmt {
+            Stmt::For(f) => f.body.yields_in_try(),
+            Stmt::Expr(e) => match (e.as_block(), e.as_try()) {
+                (Some(block), _) => block.yields_in_try(),
+                (_, Some(try_expr)) => try_expr.blocks().any(Self::contains_yield),
+                _ => false,
+            },
+            _ => false,
+        })
+    }
+}
+
+enum Frame {
+    /// A block being run statement by statement, in its own scope.
+    Block { stmts: Vec<Stmt>, pc: usize },
+    /// A `for` loop, whose body is pushed as a block for every element.
+    For {
+        var: Identifier,
+        iter: Iter,
+        body: Block,
+    },
+}
+
+/// The suspended state of a generator function call. Instead of evaluating its body recursively,
+/// a generator keeps an explicit stack of the blocks and loops it's in, so that it can stop after
+/// any statement and later pick up where it left off.
+///
+/// Only statements of the body, and of the blocks and loops nested in it, can yield. A `yield`
+/// nested inside an expression, such as `bind x = { yield 1 }`, is an error, and one inside a
+/// `try` is rejected when the function is parsed.
+pub struct Generator {
+    env: Env,
+    frames: Vec<Frame>,
+}
+
+impl Generator {
+    pub(crate) fn new(body: &Block, env: Env) -> Self {
+        let mut generator = Self {
+            env,
+            frames: vec![Frame::Block {
+                stmts: body.stmts.clone(),
+                pc: 0,
+            }],
+        };
+
+        generator.enter_scope();
+        generator
+    }
+
+    fn enter_scope(&mut self) {
+        self.env = Env::from_parent(std::mem::take(&mut self.env));
+    }
+
+    fn exit_scope(&mut self) {
+        let parent = self
+            .env
+            .parent
+            .take()
+            .expect("every frame has its own scope");
+        self.env = *parent;
+    }
+
+    /// Runs the body until it yields the next value, or returns `None` once it's done.
+    pub fn resume(&mut self) -> Result<Option<Val>, EvalError> {
+        let out = self.run();
+
+        // a generator that failed can't be resumed
+        if out.is_err() {
+            self.frames.clear();
+        }
+
+        out
+    }
+
+    fn run(&mut self) -> Result<Option<Val>, EvalError> {
+        while let Some(frame) = self.frames.last_mut() {
+            match frame {
+                Frame::Block { stmts, pc } => {
+                    let Some(stmt) = stmts.get(*pc).cloned() else {
+                        self.frames.pop();
+                        self.exit_scope();
+                        continue;
+                    };
+                    *pc += 1;
+
+                    match self.step(stmt) {
+                        Ok(Some(v)) => return Ok(Some(v)),
+                        Ok(None) => (),
+                        Err(EvalError::Break) => self.unwind(EvalError::Break)?,
+                        Err(EvalError::Continue) => self.unwind(EvalError::Continue)?,
+                        // a generator that returns early is simply done
+                        Err(EvalError::Return(_)) => self.frames.clear(),
+                        Err(e) => return Err(e),
+                    }
+                }
+                Frame::For { var, iter, body } => {
+                    let Some(item) = iter.next()? else {
+                        self.frames.pop();
+                        continue;
+                    };
+
+                    let (var, stmts) = (var.clone(), body.stmts.clone());
+                    self.frames.push(Frame::Block { stmts, pc: 0 });
+                    self.enter_scope();
+                    self.env.store_binding(var, item, false);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Runs a single statement, descending into blocks and loops instead of evaluating them.
+    fn step(&mut self, stmt: Stmt) -> Result<Option<Val>, EvalError> {
+        match stmt {
+            Stmt::Yield(y) => y.expr.eval(&mut self.env).map(Some),
+            Stmt::For(f) => {
+                let iter = Iter::from_val(f.iterable.eval(&mut self.env)?)?;
+                self.frames.push(Frame::For {
+                    var: f.var,
+                    iter,
+                    body: f.body,
+                });
+                Ok(None)
+            }
+            Stmt::Expr(e) if e.as_block().is_some() => {
+                let stmts = e.as_block().unwrap().stmts.clone();
+                self.frames.push(Frame::Block { stmts, pc: 0 });
+                self.enter_scope();
+                Ok(None)
+            }
+            stmt => stmt.eval(&mut self.env).map(|_| None),
+        }
+    }
+
+    /// Pops frames up to the innermost loop, for `break` or `continue`. The loop itself is popped
+    /// as well on `break`.
+    fn unwind(&mut self, signal: EvalError) -> Result<(), EvalError> {
+        while let Some(frame) = self.frames.last() {
+            match frame {
+                Frame::Block { .. } => {
+                    self.frames.pop();
+                    self.exit_scope();
+                }
+                Frame::For { .. } => {
+                    if signal == EvalError::Break {
+                        self.frames.pop();
+                    }
+
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(signal.outside_loop())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalError, ParseError, Parsed,
+        test_utils::{eval_src, reals},
+        val::Val,
+    };
+
+    #[test]
+    fn yield_from_loop() {
+        assert_eq!(
+            eval_src(
+                "func squares(n) => { for i in 0..n { yield i * i } }
+                squares(4).collect()"
+            ),
+            Ok(reals(&[0., 1., 4., 9.]))
+        )
+    }
+
+    #[test]
+    fn generator_resumes_on_next() {
+        assert_eq!(
+            eval_src(
+                "func gen() => { bind x = 1; yield x; x += 1; yield x; { yield x * 10 } }
+                bind g = gen()
+                [g.next(), g.next(), g.next(), g.next()]"
+            ),
+            Ok(Val::List(vec![
+                Val::Real(1.),
+                Val::Real(2.),
+                Val::Real(20.),
+                Val::Nil
+            ]))
+        )
+    }
+
+    #[test]
+    fn generator_is_lazy() {
+        // the generator never finishes, so it must only run as far as it's asked to
+        assert_eq!(
+            eval_src(
+                "func naturals() => { bind all = 0..; for i in all { yield i } }
+                naturals().map(func(x) => x * 2).take(3).collect()"
+            ),
+            Ok(reals(&[0., 2., 4.]))
+        )
+    }
+
+    #[test]
+    fn break_and_continue_in_generator() {
+        assert_eq!(
+            eval_src(
+                "func gen() => { for i in 0..10 { yield i; break }; for i in 0..3 { continue; yield i }; yield 100 }
+                gen().collect()"
+            ),
+            Ok(reals(&[0., 100.]))
+        )
+    }
+
+    #[test]
+    fn yield_outside_generator() {
+        assert_eq!(eval_src("yield 1"), Err(EvalError::YieldOutsideGenerator));
+        assert_eq!(
+            eval_src("func gen() => { bind x = { yield 1 } }; gen().collect()"),
+            Err(EvalError::YieldOutsideGenerator)
+        )
+    }
+
+    #[test]
+    fn yield_inside_try() {
+        assert_eq!(
+            Parsed::parse("func gen() => { try { yield 1 } catch e { 0 } }; gen().next()")
+                .map(|_| ()),
+            Err(ParseError::InvalidSequence {
+                expected: "no `yield` inside `try`, since generators can't be suspended there"
+                    .into(),
+                received: "yield".into()
+            })
+        );
+        assert!(
+            Parsed::parse("func gen() => { for x in [1] { try { 1 } finally { yield x } } }")
+                .is_err()
+        );
+        // a function expression inside a `try` is a generator of its own
+        assert!(
+            Parsed::parse(
+                "func f() => { try { bind g = func(x) => { yield x }; g } catch e { 0 } }"
+            )
+            .is_ok()
+        )
+    }
+}
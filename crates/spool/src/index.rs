@@ -0,0 +1,176 @@
+use crate::{
+    Eval, EvalError, Parse,
+    expr::Expr,
+    utils::{extract_whitespace, tag},
+    val::Val,
+};
+
+const INDEX_OPEN: &str = "[";
+const INDEX_CLOSE: &str = "]";
+
+/// Converts `x` to an index, failing if it's negative or not a whole number.
+pub(crate) fn expect_index(x: f32) -> Result<usize, EvalError> {
+    if x.fract() != 0. || x.is_nan() {
+        return Err(EvalError::InvalidType {
+            expected: "an integer index".into(),
+            received: x.to_string(),
+        });
+    }
+
+    if x < 0. {
+        return Err(EvalError::IndexOutOfBounds {
+            index: x as i64,
+            len: 0,
+        });
+    }
+
+    Ok(x as usize)
+}
+
+/// Indexing and slicing, such as `xs[0]`, `s[..5]` or `map["key"]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexExpr {
+    pub target: Expr,
+    pub index: Expr,
+}
+
+impl IndexExpr {
+    /// Parses the `[index]` part that follows the target. The bracket must directly follow the
+    /// target, so that a list literal on the next line isn't mistaken for an index.
+    pub(crate) fn parse_subscript(s: &str) -> crate::ParseOutput<Expr> {
+        let s = tag(INDEX_OPEN, s)?;
+        let (s, index) = Expr::parse(&s)?;
+
+        let (_, s) = extract_whitespace(&s);
+        let s = tag(INDEX_CLOSE, &s)?;
+
+        Ok((s, index))
+    }
+}
+
+impl Eval for IndexExpr {
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, EvalError> {
+        let target = self.target.eval(env)?;
+        let index = self.index.eval(env)?;
+
+        match (target, index) {
+            (Val::List(items), Val::Real(i)) => {
+                let len = items.len();
+                items
+                    .into_iter()
+                    .nth(expect_index(i)?)
+                    .ok_or(EvalError::IndexOutOfBounds {
+                        index: i as i64,
+                        len,
+                    })
+            }
+            (Val::List(items), Val::Range(r)) => Ok(Val::List(
+                r.slice_indices(items.len())?
+                    .map(|i| items[i].clone())
+                    .collect(),
+            )),
+            (Val::Str(s), Val::Real(i)) => {
+                let len = s.chars().count();
+                s.chars()
+                    .nth(expect_index(i)?)
+                    .map(|c| Val::Str(c.into()))
+                    .ok_or(EvalError::IndexOutOfBounds {
+                        index: i as i64,
+                        len,
+                    })
+            }
+            (Val::Str(s), Val::Range(r)) => {
+                let chars: Vec<char> = s.chars().collect();
+                Ok(Val::Str(
+                    r.slice_indices(chars.len())?.map(|i| chars[i]).collect(),
+                ))
+            }
+            (Val::Map(mut map), Val::Str(key)) => {
+                map.remove(&key).ok_or(EvalError::KeyNotFound(key))
+            }
+            (Val::Map(_), v) => Err(EvalError::InvalidType {
+                expected: "a string as map key".into(),
+                received: v.get_type().into(),
+            }),
+            (Val::List(_) | Val::Str(_), v) => Err(EvalError::InvalidType {
+                expected: "a real number or range as index".into(),
+                received: v.get_type().into(),
+            }),
+            (v, _) => Err(EvalError::InvalidType {
+                expected: "a list, string or map to index".into(),
+                received: v.get_type().into(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvalError, test_utils::eval_src, val::Val};
+
+    #[test]
+    fn index_list() {
+        assert_eq!(
+            eval_src("bind xs = [1, [2, 3]]; xs[1][0]"),
+            Ok(Val::Real(2.))
+        );
+        assert_eq!(
+            eval_src("[1, 2][2]"),
+            Err(EvalError::IndexOutOfBounds { index: 2, len: 2 })
+        )
+    }
+
+    #[test]
+    fn index_in_arith() {
+        assert_eq!(
+            eval_src("bind xs = [1, 2]; xs[0] + xs[1]"),
+            Ok(Val::Real(3.))
+        )
+    }
+
+    #[test]
+    fn index_map() {
+        assert_eq!(eval_src("[\"a\": 1][\"a\"]"), Ok(Val::Real(1.)));
+        assert_eq!(
+            eval_src("[\"a\": 1][\"b\"]"),
+            Err(EvalError::KeyNotFound("b".into()))
+        )
+    }
+
+    #[test]
+    fn slice_list() {
+        assert_eq!(
+            eval_src("bind xs = [0, 1, 2, 3]; xs[1..3]"),
+            Ok(Val::List(vec![Val::Real(1.), Val::Real(2.)]))
+        );
+        assert_eq!(
+            eval_src("[0, 1, 2, 3, 4][..=4 step 2]"),
+            Ok(Val::List(vec![Val::Real(0.), Val::Real(2.), Val::Real(4.)]))
+        );
+        assert_eq!(
+            eval_src("[0, 1][1..5]"),
+            Err(EvalError::IndexOutOfBounds { index: 5, len: 2 })
+        )
+    }
+
+    #[test]
+    fn slice_str() {
+        assert_eq!(
+            eval_src("\"Hello, world\"[..5]"),
+            Ok(Val::Str("Hello".into()))
+        );
+        assert_eq!(eval_src("\"Hello\"[1]"), Ok(Val::Str("e".into())));
+        assert_eq!(eval_src("\"Hello\"[3..]"), Ok(Val::Str("lo".into())))
+    }
+
+    #[test]
+    fn index_non_integer() {
+        assert_eq!(
+            eval_src("[1][0.5]"),
+            Err(EvalError::InvalidType {
+                expected: "an integer index".into(),
+                received: "0.5".into()
+            })
+        )
+    }
+}
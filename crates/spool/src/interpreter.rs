@@ -0,0 +1,213 @@
+use std::{path::Path, sync::Arc};
+
+use crate::{
+    Env, EvalError, Parsed, binding::Identifier, builtins, fn_call::CallArgs, module::ModuleCtx,
+    native::NativeExport, val::Val,
+};
+
+/// The entry point for embedding Spool. An interpreter holds the global scope that every script
+/// it evaluates shares, so bindings and functions defined by one are visible to the next.
+///
+/// Interpreters are `Send`, so they can be moved to other threads. Separate interpreters share no
+/// state, and can run in parallel.
+#[derive(Debug, Default)]
+pub struct Interpreter {
+    env: Env,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses and evaluates `src`, returning the value of its last statement. Imports are
+    /// resolved relative to the file evaluated last, or the working directory.
+    pub fn eval_str(&mut self, src: &str) -> Result<Val, EvalError> {
+        let (_, parsed) = Parsed::parse(src).map_err(EvalError::Parse)?;
+        parsed.eval(&mut self.env)
+    }
+
+    /// Evaluates the file at `path`, whose imports are resolved relative to it. Modules it
+    /// imports are cached across calls.
+    pub fn eval_file(&mut self, path: impl AsRef<Path>) -> Result<Val, EvalError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+
+        let src = std::fs::read_to_string(path)
+            .map_err(|_| EvalError::ModuleNotFound(display.clone()))?;
+        let (_, parsed) = Parsed::parse(&src).map_err(|error| EvalError::ModuleParse {
+            module: display,
+            error,
+        })?;
+
+        let loader = self.env.module_ctx().loader.clone();
+        let ctx = Arc::new(ModuleCtx {
+            file: Some(path.into()),
+            loader,
+        });
+        self.env.module = Some(ctx.clone());
+
+        ctx.eval_file(&parsed, &mut self.env)
+    }
+
+    /// Calls the global function `name` with positional `args`. Structs are constructed, and
+    /// built-in functions are called if no global of that name exists.
+    pub fn call_function(&mut self, name: &str, args: Vec<Val>) -> Result<Val, EvalError> {
+        let id = Identifier(name.into());
+        let args = CallArgs::positional(args);
+
+        match self.env.get_stored(&id) {
+            Err(EvalError::IdentifierNotFound(id)) => match builtins::lookup(&id, &mut self.env) {
+                Some(builtin) => builtin(args),
+                None => Err(EvalError::IdentifierNotFound(id)),
+            },
+            callee => callee?.call(args),
+        }
+    }
+
+    /// Returns the value of the global `name`. Functions are returned as function values.
+    pub fn get_global(&self, name: &str) -> Result<Val, EvalError> {
+        self.env.get_stored(&Identifier(name.into()))?.into_val()
+    }
+
+    /// Binds `val` to the global `name`, replacing any previous definition, even a final one.
+    pub fn set_global(&mut self, name: &str, val: Val) {
+        self.env.store_binding(Identifier(name.into()), val, false);
+    }
+
+    /// See [`Env::register_native`].
+    pub fn register_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        func: impl Fn(&[Val]) -> Result<Val, EvalError> + Send + Sync + 'static,
+    ) {
+        self.env.register_native(name, arity, func);
+    }
+
+    /// See [`Env::register_export`].
+    pub fn register_export<F: NativeExport>(&mut self) {
+        self.env.register_export::<F>();
+    }
+
+    /// See [`Env::allow_file_access`].
+    pub fn allow_file_access(&mut self) {
+        self.env.allow_file_access();
+    }
+
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    pub fn env_mut(&mut self) -> &mut Env {
+        &mut self.env
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvalError, Interpreter, ParseError, Val, test_utils::Fixture};
+
+    #[test]
+    fn globals_persist() {
+        let mut interp = Interpreter::new();
+
+        assert_eq!(
+            interp.eval_str("bind x = 2; func double(n) => n * 2"),
+            Ok(Val::Unit)
+        );
+        assert_eq!(interp.eval_str("double(x)"), Ok(Val::Real(4.)));
+        assert_eq!(interp.get_global("x"), Ok(Val::Real(2.)))
+    }
+
+    #[test]
+    fn call_function() {
+        let mut interp = Interpreter::new();
+        interp
+            .eval_str("func greet(name, greeting = \"hi\") => [greeting, name]")
+            .unwrap();
+
+        assert_eq!(
+            interp.call_function("greet", vec![Val::Str("Ann".into())]),
+            Ok(Val::List(vec![
+                Val::Str("hi".into()),
+                Val::Str("Ann".into())
+            ]))
+        );
+        assert_eq!(
+            interp.call_function("parseReal", vec![Val::Str("1".into())]),
+            Ok(Val::ok(Val::Real(1.)))
+        );
+        assert_eq!(
+            interp.call_function("nope", vec![]),
+            Err(EvalError::IdentifierNotFound("nope".into()))
+        )
+    }
+
+    #[test]
+    fn set_global() {
+        let mut interp = Interpreter::new();
+        interp.set_global("limit", Val::Real(3.));
+
+        assert_eq!(interp.eval_str("limit * 2"), Ok(Val::Real(6.)));
+        assert_eq!(
+            interp.get_global("missing"),
+            Err(EvalError::IdentifierNotFound("missing".into()))
+        )
+    }
+
+    #[test]
+    fn eval_str_parse_error() {
+        assert_eq!(
+            Interpreter::new().eval_str("bind x 1"),
+            Err(EvalError::Parse(ParseError::SequenceNotFound {
+                expected: "=".into(),
+                received: "1".into()
+            }))
+        )
+    }
+
+    #[test]
+    fn eval_file() {
+        let files = Fixture::new(&[
+            ("main.spool", "import util; bind r = util.area(2)"),
+            ("util.spool", "pub func area(r) => r * r * 3"),
+        ]);
+
+        let mut interp = Interpreter::new();
+        interp.eval_file(files.path("main.spool")).unwrap();
+
+        assert_eq!(interp.get_global("r"), Ok(Val::Real(12.)));
+        assert_eq!(
+            interp.eval_file(files.path("nope.spool")),
+            Err(EvalError::ModuleNotFound(
+                files.path("nope.spool").display().to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn parallel_instances() {
+        let handles: Vec<_> = (0..4)
+            .map(|n| {
+                let mut interp = Interpreter::new();
+                interp.set_global("n", Val::Real(n as f32));
+                interp.register_native("offset", 1, move |args| match args[0] {
+                    Val::Real(r) => Ok(Val::Real(r + 100.)),
+                    _ => Ok(Val::Nil),
+                });
+                interp.eval_str("struct Acc { total = 0 }").unwrap();
+
+                std::thread::spawn(move || {
+                    interp
+                        .eval_str("bind acc = Acc(); for i in 0..=n { acc.total += i }")
+                        .unwrap();
+                    interp.eval_str("offset(acc.total)")
+                })
+            })
+            .collect();
+
+        let totals: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(totals, [100., 101., 103., 106.].map(|r| Ok(Val::Real(r))))
+    }
+}
@@ -0,0 +1,361 @@
+use std::sync::{Arc, Mutex, TryLockError};
+
+use crate::{
+    EvalError,
+    binding::Identifier,
+    fn_call::{CallArgs, call_val, expect_args},
+    generator::Generator,
+    index::expect_index,
+    val::Val,
+};
+
+/// The iterator protocol. Anything that can be iterated over is turned into an `Iter`: lists,
+/// strings, maps and ranges, iterator values, and structs with a `next()` method. A struct's
+/// `next()` signals that it's exhausted by returning `nil` (or `()`), and calling `next()` on an
+/// exhausted iterator returns `nil`.
+///
+/// Adapters such as `map` and `filter` wrap another `Iter` and do their work one element at a
+/// time, so chaining them never builds intermediate lists.
+pub enum Iter {
+    Seq(Box<dyn Iterator<Item = Val> + Send>),
+    Shared(IterRef),
+    User(Val),
+    Map(Box<Iter>, Val),
+    Filter(Box<Iter>, Val),
+    Take(Box<Iter>, usize),
+    Skip(Box<Iter>, usize),
+    Zip(Box<Iter>, Box<Iter>),
+    Enumerate(Box<Iter>, usize),
+    Gen(Box<Generator>),
+}
+
+impl Iter {
+    pub fn from_val(val: Val) -> Result<Self, EvalError> {
+        match val {
+            Val::Iter(iter) => Ok(Self::Shared(iter)),
+            Val::Struct(ref inst)
+                if inst
+                    .lock()
+                    .unwrap()
+                    .method(&Identifier("next".into()))
+                    .is_some() =>
+            {
+                Ok(Self::User(val))
+            }
+            v => v.iterate().map(Self::Seq),
+        }
+    }
+
+    pub fn next(&mut self) -> Result<Option<Val>, EvalError> {
+        match self {
+            Self::Seq(iter) => Ok(iter.next()),
+            Self::Shared(iter) => match iter.0.try_lock() {
+                Ok(mut iter) => iter.next(),
+                Err(TryLockError::WouldBlock) => Err(EvalError::IteratorInUse),
+                Err(TryLockError::Poisoned(_)) => Err(EvalError::IteratorPoisoned),
+            },
+            Self::User(inst) => {
+                let Val::Struct(ref instance) = *inst else {
+                    unreachable!("user iterators are always struct instances")
+                };
+
+                let next = instance
+                    .lock()
+                    .unwrap()
+                    .method(&Identifier("next".into()))
+                    .unwrap();
+                match next.call(CallArgs::positional(vec![inst.clone()]))? {
+                    Val::Nil | Val::Unit => Ok(None),
+                    v => Ok(Some(v)),
+                }
+            }
+            Self::Map(iter, f) => match iter.next()? {
+                Some(v) => call_val(f, CallArgs::positional(vec![v])).map(Some),
+                None => Ok(None),
+            },
+            Self::Filter(iter, pred) => {
+                while let Some(v) = iter.next()? {
+                    match call_val(pred, CallArgs::positional(vec![v.clone()]))? {
+                        Val::Bool(true) => return Ok(Some(v)),
+                        Val::Bool(false) => (),
+                        r => {
+                            return Err(EvalError::InvalidType {
+                                expected: "a boolean from the filter predicate".into(),
+                                received: r.get_type().into(),
+                            });
+                        }
+                    }
+                }
+
+                Ok(None)
+            }
+            Self::Take(iter, n) => {
+                if *n == 0 {
+                    return Ok(None);
+                }
+
+                *n -= 1;
+                iter.next()
+            }
+            Self::Skip(iter, n) => {
+                while *n > 0 {
+                    *n -= 1;
+                    if iter.next()?.is_none() {
+                        return Ok(None);
+                    }
+                }
+
+                iter.next()
+            }
+            Self::Zip(a, b) => match (a.next()?, b.next()?) {
+                (Some(a), Some(b)) => Ok(Some(Val::List(vec![a, b]))),
+                _ => Ok(None),
+            },
+            Self::Gen(generator) => generator.resume(),
+            Self::Enumerate(iter, idx) => match iter.next()? {
+                Some(v) => {
+                    *idx += 1;
+                    Ok(Some(Val::List(vec![Val::Real((*idx - 1) as f32), v])))
+                }
+                None => Ok(None),
+            },
+        }
+    }
+
+    pub fn collect(mut self) -> Result<Vec<Val>, EvalError> {
+        let mut items = vec![];
+
+        while let Some(v) = self.next()? {
+            items.push(v);
+        }
+
+        Ok(items)
+    }
+}
+
+/// A shared, stateful iterator value. Clones refer to the same iterator, so advancing one
+/// advances all of them.
+#[derive(Clone)]
+pub struct IterRef(pub(crate) Arc<Mutex<Iter>>);
+
+impl IterRef {
+    pub fn new(iter: Iter) -> Self {
+        Self(Arc::new(Mutex::new(iter)))
+    }
+}
+
+impl PartialEq for IterRef {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Debug for IterRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Iter(..)")
+    }
+}
+
+fn expect_count(val: Val) -> Result<usize, EvalError> {
+    match val {
+        Val::Real(n) => expect_index(n),
+        v => Err(EvalError::InvalidType {
+            expected: "a real number".into(),
+            received: v.get_type().into(),
+        }),
+    }
+}
+
+/// The built-in methods available on every iterable value.
+pub(crate) const ITER_METHODS: &[&str] = &[
+    "next",
+    "map",
+    "filter",
+    "take",
+    "skip",
+    "zip",
+    "enumerate",
+    "fold",
+    "collect",
+];
+
+/// Calls the built-in iterator method `method` on `target`, which may be any iterable value.
+pub(crate) fn call_iter_method(
+    target: Val,
+    method: &Identifier,
+    args: CallArgs,
+) -> Result<Val, EvalError> {
+    let ty = target.get_type();
+    let wrap = |iter: Iter| Ok(Val::Iter(IterRef::new(iter)));
+
+    match method.0.as_str() {
+        "next" => {
+            expect_args(args, [])?;
+            Ok(Iter::from_val(target)?.next()?.unwrap_or(Val::Nil))
+        }
+        "map" => {
+            let [f] = expect_args(args, ["f"])?;
+            wrap(Iter::Map(Iter::from_val(target)?.into(), f))
+        }
+        "filter" => {
+            let [pred] = expect_args(args, ["pred"])?;
+            wrap(Iter::Filter(Iter::from_val(target)?.into(), pred))
+        }
+        "take" => {
+            let [n] = expect_args(args, ["n"])?;
+            wrap(Iter::Take(Iter::from_val(target)?.into(), expect_count(n)?))
+        }
+        "skip" => {
+            let [n] = expect_args(args, ["n"])?;
+            wrap(Iter::Skip(Iter::from_val(target)?.into(), expect_count(n)?))
+        }
+        "zip" => {
+            let [other] = expect_args(args, ["other"])?;
+            wrap(Iter::Zip(
+                Iter::from_val(target)?.into(),
+                Iter::from_val(other)?.into(),
+            ))
+        }
+        "enumerate" => {
+            expect_args(args, [])?;
+            wrap(Iter::Enumerate(Iter::from_val(target)?.into(), 0))
+        }
+        "fold" => {
+            let [init, f] = expect_args(args, ["init", "f"])?;
+            let mut iter = Iter::from_val(target)?;
+            let mut acc = init;
+
+            while let Some(v) = iter.next()? {
+                acc = call_val(&f, CallArgs::positional(vec![acc, v]))?;
+            }
+
+            Ok(acc)
+        }
+        "collect" => {
+            expect_args(args, [])?;
+            Ok(Val::List(Iter::from_val(target)?.collect()?))
+        }
+        _ => Err(EvalError::UnknownMember {
+            ty: ty.into(),
+            member: method.clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalError,
+        test_utils::{eval_src, reals},
+        val::Val,
+    };
+
+    use super::{Iter, IterRef};
+
+    #[test]
+    fn map_filter_collect() {
+        assert_eq!(
+            eval_src(
+                "[1, 2, 3, 4].map(func(x) => x * 10).filter(func(x) => x in [20, 40]).collect()"
+            ),
+            Ok(reals(&[20., 40.]))
+        )
+    }
+
+    #[test]
+    fn adapters_are_lazy() {
+        // an unbounded range would never finish if any adapter materialized it
+        assert_eq!(
+            eval_src("{ 0.. }.skip(2).map(func(x) => x * x).take(3).collect()"),
+            Ok(reals(&[4., 9., 16.]))
+        )
+    }
+
+    #[test]
+    fn fold() {
+        assert_eq!(
+            eval_src("{ 1..=4 }.fold(0, func(acc, x) => acc + x)"),
+            Ok(Val::Real(10.))
+        );
+        assert_eq!(
+            eval_src("\"abc\".fold(init: \"\", f: func(acc, c) => c)"),
+            Ok(Val::Str("c".into()))
+        )
+    }
+
+    #[test]
+    fn zip_and_enumerate() {
+        assert_eq!(
+            eval_src("\"ab\".enumerate().collect()"),
+            Ok(Val::List(vec![
+                Val::List(vec![Val::Real(0.), Val::Str("a".into())]),
+                Val::List(vec![Val::Real(1.), Val::Str("b".into())])
+            ]))
+        );
+        assert_eq!(
+            eval_src("[1, 2, 3].zip(10..).map(func(p) => p[0] + p[1]).collect()"),
+            Ok(reals(&[11., 13., 15.]))
+        )
+    }
+
+    #[test]
+    fn iterator_value_is_stateful() {
+        assert_eq!(
+            eval_src("bind it = [1, 2, 3].map(func(x) => x); it.next(); it.collect()"),
+            Ok(reals(&[2., 3.]))
+        )
+    }
+
+    #[test]
+    fn user_iterator() {
+        let src = "struct Counter { n = 0; func next(self) => { self.n += 1; self.n } }
+            bind sum = 0
+            for x in Counter().take(3) { sum += x }
+            [sum, Counter().map(func(x) => x * 2).take(2).collect()]";
+
+        assert_eq!(
+            eval_src(src),
+            Ok(Val::List(vec![Val::Real(6.), reals(&[2., 4.])]))
+        )
+    }
+
+    #[test]
+    fn user_iterator_ends_on_unit() {
+        assert_eq!(
+            eval_src("struct Empty { func next(self) => {} }; Empty().collect()"),
+            Ok(Val::List(vec![]))
+        )
+    }
+
+    #[test]
+    fn unknown_method() {
+        assert_eq!(
+            eval_src("[1].frobnicate()"),
+            Err(EvalError::UnknownMember {
+                ty: "List".into(),
+                member: "frobnicate".into()
+            })
+        )
+    }
+
+    #[test]
+    fn advance_iterator_in_use() {
+        assert_eq!(
+            eval_src("bind it = [1, 2].map(func(x) => it.next()); it.next()"),
+            Err(EvalError::IteratorInUse)
+        )
+    }
+
+    #[test]
+    fn poisoned_iterator() {
+        let iter = IterRef::new(Iter::Seq(Box::new(std::iter::empty())));
+        let shared = iter.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = shared.0.lock().unwrap();
+            panic!("advancing the iterator failed");
+        })
+        .join();
+
+        assert_eq!(Iter::Shared(iter).next(), Err(EvalError::IteratorPoisoned))
+    }
+}
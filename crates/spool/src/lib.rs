@@ -1,23 +1,59 @@
-use binding::Identifier;
+pub use binding::Identifier;
+pub use conv::{Fields, FromVal, IntoVal};
+#[cfg(feature = "serde")]
+pub use de::{DeError, from_env, from_file, from_val};
 pub use env::Env;
-use stmt::Stmt;
+pub use error::ErrorVal;
+pub use interpreter::Interpreter;
+pub use native::{IntoNativeResult, NativeExport, export_arg};
+#[cfg(feature = "derive")]
+pub use spool_derive::{FromVal, IntoVal, export};
+use stmt::{Stmt, parse_stmts};
 
 use std::num::{ParseFloatError, ParseIntError};
-use val::Val;
+pub use val::Val;
 
 pub(crate) mod binding;
 pub(crate) mod block;
+pub(crate) mod builtins;
+pub(crate) mod coalesce;
+pub(crate) mod conv;
+#[cfg(feature = "serde")]
+mod de;
 pub(crate) mod env;
+pub(crate) mod error;
 pub(crate) mod expr;
 mod fn_call;
 pub mod func;
+pub(crate) mod generator;
+pub(crate) mod index;
+pub(crate) mod interpreter;
+pub(crate) mod iter;
+pub(crate) mod list;
 pub(crate) mod lit;
+pub(crate) mod loops;
+pub(crate) mod map;
+pub(crate) mod math;
+pub(crate) mod member;
+pub(crate) mod membership;
+pub(crate) mod module;
+pub(crate) mod native;
+pub(crate) mod range;
 mod reassignment;
+pub(crate) mod result;
+#[cfg(feature = "serde")]
+mod ser;
 pub(crate) mod stmt;
+pub(crate) mod structs;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub(crate) mod utils;
 pub(crate) mod val;
 
-const KEYWORDS: &[&str] = &["func", "bind", "final"];
+const KEYWORDS: &[&str] = &[
+    "func", "nil", "import", "bind", "final", "for", "in", "break", "continue", "step", "struct",
+    "yield", "try", "catch", "finally", "throw", "pub",
+];
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -33,29 +69,115 @@ pub trait Parse: Sized {
     fn parse(s: &str) -> ParseOutput<Self>;
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum EvalError {
     IdentifierNotFound(Identifier),
     InvalidStoredType,
-    InvalidArgumentLen,
+    TooManyArguments {
+        expected: usize,
+        received: usize,
+    },
+    /// Raised by native functions, which have no named parameters to report as missing.
+    TooFewArguments {
+        expected: usize,
+        received: usize,
+    },
+    MissingArgument(Identifier),
+    UnknownArgument(Identifier),
+    DuplicateArgument(Identifier),
     ImmutableReassignment(Identifier),
-    InvalidType { expected: String, received: String },
+    InvalidType {
+        expected: String,
+        received: String,
+    },
+    NotIterable(String),
+    IndexOutOfBounds {
+        index: i64,
+        len: usize,
+    },
+    KeyNotFound(String),
+    InvalidRangeStep,
+    UnknownMember {
+        ty: String,
+        member: Identifier,
+    },
+    YieldOutsideGenerator,
+    /// An iterator was advanced while it was already producing an element, e.g. from its own
+    /// `map` function.
+    IteratorInUse,
+    /// An iterator was advanced after a previous call advancing it panicked, which may have left
+    /// it in an inconsistent state.
+    IteratorPoisoned,
+    ModuleNotFound(String),
+    ModuleParse {
+        module: String,
+        error: ParseError,
+    },
+    /// The chain of modules that import each other, starting and ending with the same one.
+    ImportCycle(Vec<String>),
+    /// An import of a member that the module doesn't export with `pub`.
+    PrivateImport {
+        module: String,
+        member: Identifier,
+    },
+    /// A declaration marked with `pub` anywhere but the top level of a module, such as in a
+    /// block or function body.
+    NestedExport(Identifier),
+    /// A script passed to [`Interpreter::eval_str`] that failed to parse.
+    Parse(ParseError),
+    /// A field that a struct or map converted to a Rust type lacks.
+    MissingField {
+        ty: String,
+        field: String,
+    },
+    /// A field that couldn't be converted to the type of the Rust field.
+    InvalidField {
+        ty: String,
+        field: String,
+        error: Box<EvalError>,
+    },
+    /// Raised by `throw`, and caught by the enclosing `try`.
+    Thrown(Val),
+    /// Raised by `?` on an `Err`, and caught by the enclosing function call.
+    Return(Val),
+    /// Raised by `break`, and caught by the enclosing loop.
+    Break,
+    /// Raised by `continue`, and caught by the enclosing loop.
+    Continue,
+    /// A `break` that escaped the function it's in, rather than ending a loop of the caller.
+    BreakOutsideLoop,
+    /// A `continue` that escaped the function it's in.
+    ContinueOutsideLoop,
 }
 
 pub trait Eval {
     fn eval(&self, env: &mut Env) -> Result<Val, EvalError>;
 }
 
-pub struct Parsed(Stmt);
+/// A sequence of top-level statements, separated by `;` or line breaks.
+pub struct Parsed(Vec<Stmt>);
 
 impl Parsed {
     pub fn parse(s: &str) -> ParseOutput<Self> {
-        let (s, stmt) = Stmt::parse(s)?;
+        let (s, stmts) = parse_stmts(s, str::is_empty)?;
 
-        Ok((s, Self(stmt)))
+        Ok((s, Self(stmts)))
     }
 
+    /// Evaluates every statement in order, returning the value of the last one, or the value
+    /// returned early by `?`.
     pub fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
-        self.0.eval(env)
+        let mut last = Val::Unit;
+
+        for stmt in &self.0 {
+            last = match stmt.eval(env) {
+                Ok(v) => v,
+                // `?` at the top level ends the script
+                Err(EvalError::Return(v)) => return Ok(v),
+                Err(e) => return Err(e.outside_loop()),
+            };
+        }
+
+        Ok(last)
     }
 }
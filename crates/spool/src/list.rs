@@ -0,0 +1,92 @@
+use crate::{
+    Eval, Parse,
+    expr::Expr,
+    utils::{extract_whitespace, tag},
+    val::Val,
+};
+
+const LIST_OPEN: &str = "[";
+const LIST_CLOSE: &str = "]";
+
+/// A list literal such as `[1, x, "three"]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ListExpr {
+    pub items: Vec<Expr>,
+}
+
+impl Parse for ListExpr {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(LIST_OPEN, &s)?;
+
+        let mut items = vec![];
+        let mut s = s;
+
+        while let Ok((new_s, item)) = Expr::parse(&s) {
+            items.push(item);
+            s = match tag(",", &extract_whitespace(&new_s).1) {
+                Ok(v) => v,
+                Err(_) => new_s,
+            };
+        }
+
+        let (_, s) = extract_whitespace(&s);
+        let s = tag(LIST_CLOSE, &s)?;
+
+        Ok((s, Self { items }))
+    }
+}
+
+impl Eval for ListExpr {
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, crate::EvalError> {
+        let items = self
+            .items
+            .iter()
+            .map(|item| item.eval(env))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Val::List(items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Env, Eval, Parse,
+        expr::Expr,
+        lit::{LitReal, Literal},
+        val::Val,
+    };
+
+    use super::ListExpr;
+
+    #[test]
+    fn parse_list() {
+        assert_eq!(
+            ListExpr::parse("[1, 2]"),
+            Ok((
+                "".into(),
+                ListExpr {
+                    items: vec![
+                        Expr::simple(Literal::Real(LitReal(1.))),
+                        Expr::simple(Literal::Real(LitReal(2.)))
+                    ]
+                }
+            ))
+        )
+    }
+
+    #[test]
+    fn eval_list() {
+        let (_, list) = ListExpr::parse("[1, \"two\", []]").unwrap();
+
+        assert_eq!(
+            list.eval(&mut Env::new()),
+            Ok(Val::List(vec![
+                Val::Real(1.),
+                Val::Str("two".into()),
+                Val::List(vec![])
+            ]))
+        )
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
-    Eval, Parse, ParseError,
-    utils::{extract_float, extract_op, extract_string, extract_whitespace},
+    Eval, EvalError, Parse, ParseError,
+    utils::{
+        extract_float, extract_inline_whitespace, extract_op, extract_string, extract_whitespace,
+        starts_with_keyword,
+    },
+    val::Val,
 };
 
 #[derive(Debug, PartialEq, Clone)]
@@ -37,9 +41,7 @@ impl Parse for LitReal {
         let (_, s) = extract_whitespace(s);
         let (unparsed, rest) = extract_float(&s);
 
-        let float = unparsed
-            .parse()
-            .map_err(|e| ParseError::ParseFloatError(e))?;
+        let float = unparsed.parse().map_err(ParseError::ParseFloatError)?;
 
         Ok((rest, LitReal(float)))
     }
@@ -58,26 +60,37 @@ impl From<bool> for LitBool {
 impl Parse for LitBool {
     fn parse(s: &str) -> crate::ParseOutput<Self> {
         let (_, s) = extract_whitespace(s);
-        if s.len() < 4 {
+
+        let inner = ["true", "false"]
+            .into_iter()
+            .find(|kw| starts_with_keyword(kw, &s))
+            .ok_or_else(|| ParseError::InvalidSequence {
+                expected: "true|false".into(),
+                received: s.clone(),
+            })?;
+
+        Ok((s[inner.len()..].into(), Self(inner == "true")))
+    }
+}
+
+/// `nil`, the absent value.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LitNil;
+
+pub(crate) const NIL_KW: &str = "nil";
+
+impl Parse for LitNil {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+
+        if !starts_with_keyword(NIL_KW, &s) {
             return Err(ParseError::SequenceNotFound {
-                expected: "boolean sequence".into(),
-                received: s.into(),
+                expected: NIL_KW.into(),
+                received: s,
             });
         }
 
-        let (unparsed, rest) = (&s[..4], &s[4..]);
-        let inner = match unparsed {
-            "true" => true,
-            "false" => false,
-            s => {
-                return Err(ParseError::InvalidSequence {
-                    expected: "true|false".into(),
-                    received: s.into(),
-                });
-            }
-        };
-
-        Ok((rest.into(), Self(inner)))
+        Ok((s[NIL_KW.len()..].into(), Self))
     }
 }
 
@@ -86,6 +99,7 @@ pub enum Literal {
     Str(LitStr),
     Real(LitReal),
     Bool(LitBool),
+    Nil(LitNil),
 }
 
 impl Parse for Literal {
@@ -94,6 +108,7 @@ impl Parse for Literal {
             .map(|(s, p)| (s, Self::Real(p)))
             .or_else(|_| LitStr::parse(s).map(|(s, p)| (s, Self::Str(p))))
             .or_else(|_| LitBool::parse(s).map(|(s, p)| (s, Self::Bool(p))))
+            .or_else(|_| LitNil::parse(s).map(|(s, p)| (s, Self::Nil(p))))
     }
 }
 
@@ -103,6 +118,7 @@ impl Eval for Literal {
             Self::Str(s) => crate::val::Val::Str(s.0.clone()),
             Self::Real(r) => crate::val::Val::Real(r.0),
             Self::Bool(b) => crate::val::Val::Bool(b.0),
+            Self::Nil(_) => crate::val::Val::Nil,
         })
     }
 }
@@ -113,11 +129,38 @@ pub enum Op {
     Sub,
     Mul,
     Div,
+    Rem,
+}
+
+impl Op {
+    pub fn apply(self, lhs: Val, rhs: Val) -> Result<Val, EvalError> {
+        let lhs = Self::expect_real(lhs)?;
+        let rhs = Self::expect_real(rhs)?;
+
+        Ok(Val::Real(match self {
+            Op::Add => lhs + rhs,
+            Op::Sub => lhs - rhs,
+            Op::Mul => lhs * rhs,
+            Op::Div => lhs / rhs,
+            Op::Rem => lhs % rhs,
+        }))
+    }
+
+    fn expect_real(val: Val) -> Result<f32, EvalError> {
+        match val {
+            Val::Real(r) => Ok(r),
+            v => Err(EvalError::InvalidType {
+                expected: "a real number".into(),
+                received: v.get_type().into(),
+            }),
+        }
+    }
 }
 
 impl Parse for Op {
+    /// Parses an operator on the same line, since a line break before one ends the statement.
     fn parse(s: &str) -> crate::ParseOutput<Self> {
-        let (_, s) = extract_whitespace(s);
+        let (_, s) = extract_inline_whitespace(s);
         let (op, rest) = extract_op(&s)?;
 
         Ok((
@@ -127,6 +170,7 @@ impl Parse for Op {
                 "-" => Op::Sub,
                 "*" => Op::Mul,
                 "/" => Op::Div,
+                "%" => Op::Rem,
                 o => panic!("parser should have returned at this point. received character {o}"),
             },
         ))
@@ -160,7 +204,23 @@ mod tests {
 
     #[test]
     fn parse_op() {
-        assert_eq!(Op::parse("+").unwrap().1, Op::Add)
+        assert_eq!(Op::parse("+").unwrap().1, Op::Add);
+        assert_eq!(Op::parse("%").unwrap().1, Op::Rem)
+    }
+
+    #[test]
+    fn apply_op() {
+        assert_eq!(
+            Op::Rem.apply(Val::Real(7.), Val::Real(4.)),
+            Ok(Val::Real(3.))
+        );
+        assert_eq!(
+            Op::Add.apply(Val::Bool(true), Val::Real(4.)),
+            Err(crate::EvalError::InvalidType {
+                expected: "a real number".into(),
+                received: "Boolean".into()
+            })
+        )
     }
 
     #[test]
@@ -186,6 +246,20 @@ mod tests {
         assert_eq!(
             Literal::parse("true"),
             Ok(("".into(), Literal::Bool(crate::lit::LitBool(true))))
-        )
+        );
+        assert_eq!(
+            Literal::parse("false"),
+            Ok(("".into(), Literal::Bool(crate::lit::LitBool(false))))
+        );
+        assert!(Literal::parse("trueish").is_err())
+    }
+
+    #[test]
+    fn parse_nil() {
+        assert_eq!(
+            Literal::parse("nil"),
+            Ok(("".into(), Literal::Nil(crate::lit::LitNil)))
+        );
+        assert!(Literal::parse("nils").is_err())
     }
 }
@@ -0,0 +1,158 @@
+use crate::{
+    Eval, EvalError, Parse, ParseError,
+    binding::Identifier,
+    block::Block,
+    expr::Expr,
+    iter::Iter,
+    utils::{extract_whitespace, starts_with_keyword, tag},
+    val::Val,
+};
+
+pub(crate) const FOR_KW: &str = "for";
+pub(crate) const IN_KW: &str = "in";
+pub(crate) const BREAK_KW: &str = "break";
+pub(crate) const CONTINUE_KW: &str = "continue";
+
+/// `for x in expr { ... }`. The loop variable is bound in a fresh child scope on every iteration.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForLoop {
+    pub var: Identifier,
+    pub iterable: Expr,
+    pub body: Block,
+}
+
+impl Parse for ForLoop {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(FOR_KW, &s)?;
+
+        let (s, var) = Identifier::parse(&s)?;
+
+        let (_, s) = extract_whitespace(&s);
+        if !starts_with_keyword(IN_KW, &s) {
+            return Err(ParseError::SequenceNotFound {
+                expected: IN_KW.into(),
+                received: s,
+            });
+        }
+
+        let (s, iterable) = Expr::parse(&s[IN_KW.len()..])?;
+        let (s, body) = Block::parse(&s)?;
+
+        Ok((
+            s,
+            Self {
+                var,
+                iterable,
+                body,
+            },
+        ))
+    }
+}
+
+impl Eval for ForLoop {
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, EvalError> {
+        let mut iter = Iter::from_val(self.iterable.eval(env)?)?;
+
+        while let Some(item) = iter.next()? {
+            let out = env.with_child(|env| {
+                env.store_binding(self.var.clone(), item, false);
+                self.body.eval(env)
+            });
+
+            match out {
+                Ok(_) | Err(EvalError::Continue) => (),
+                Err(EvalError::Break) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(Val::Unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvalError, Parsed, test_utils::eval_src, val::Val};
+
+    #[test]
+    fn parse_for_loop() {
+        let (s, _) = Parsed::parse("for x in [1, 2] { x }").unwrap();
+        assert_eq!(s, "")
+    }
+
+    #[test]
+    fn for_over_list() {
+        assert_eq!(
+            eval_src("bind sum = 0; for x in [1, 2, 3] { sum += x }; sum"),
+            Ok(Val::Real(6.))
+        )
+    }
+
+    #[test]
+    fn for_over_str() {
+        assert_eq!(
+            eval_src(
+                "bind n = 0; bind last = \"\"; for c in \"abc\" { n += 1; last = c }; [n, last]"
+            ),
+            Ok(Val::List(vec![Val::Real(3.), Val::Str("c".into())]))
+        )
+    }
+
+    #[test]
+    fn for_over_map_entries() {
+        assert_eq!(
+            eval_src("bind last = 0; for entry in [\"a\": 1, \"b\": 2] { last = entry }; last"),
+            Ok(Val::List(vec![Val::Str("b".into()), Val::Real(2.)]))
+        )
+    }
+
+    #[test]
+    fn for_break_and_continue() {
+        assert_eq!(
+            eval_src("bind n = 0; for x in [1, 2, 3] { n += 1; break; n += 10 }; n"),
+            Ok(Val::Real(1.))
+        );
+        assert_eq!(
+            eval_src("bind n = 0; for x in [1, 2, 3] { n += 1; continue; n += 10 }; n"),
+            Ok(Val::Real(3.))
+        )
+    }
+
+    #[test]
+    fn for_loop_var_is_scoped() {
+        assert_eq!(
+            eval_src("for x in [1] { x }; x"),
+            Err(EvalError::IdentifierNotFound("x".into()))
+        )
+    }
+
+    #[test]
+    fn for_over_non_iterable() {
+        assert_eq!(
+            eval_src("for x in 5 { x }"),
+            Err(EvalError::NotIterable("Real number".into()))
+        )
+    }
+
+    #[test]
+    fn break_outside_loop() {
+        assert_eq!(eval_src("break"), Err(EvalError::BreakOutsideLoop));
+        assert_eq!(
+            eval_src("bind x = { continue }"),
+            Err(EvalError::ContinueOutsideLoop)
+        )
+    }
+
+    #[test]
+    fn break_in_called_function() {
+        assert_eq!(
+            eval_src("bind n = 0; func f() => { break }; for x in [1, 2, 3] { n = n + 1; f() }; n"),
+            Err(EvalError::BreakOutsideLoop)
+        );
+        assert_eq!(
+            eval_src("func f() => { continue }; for x in [1] { f() }"),
+            Err(EvalError::ContinueOutsideLoop)
+        )
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    Eval, EvalError, Parse,
+    expr::Expr,
+    utils::{extract_whitespace, tag},
+    val::Val,
+};
+
+const MAP_OPEN: &str = "[";
+const MAP_CLOSE: &str = "]";
+const ENTRY_SEPARATOR: &str = ":";
+
+/// A map literal such as `["a": 1, "b": 2]`, or `[:]` for an empty map. Keys are expressions that
+/// must evaluate to strings.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MapExpr {
+    pub entries: Vec<(Expr, Expr)>,
+}
+
+impl MapExpr {
+    fn parse_entry(s: &str) -> crate::ParseOutput<(Expr, Expr)> {
+        let (s, key) = Expr::parse(s)?;
+        let (_, s) = extract_whitespace(&s);
+        let s = tag(ENTRY_SEPARATOR, &s)?;
+        let (s, val) = Expr::parse(&s)?;
+
+        Ok((s, (key, val)))
+    }
+}
+
+impl Parse for MapExpr {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(MAP_OPEN, &s)?;
+
+        let (_, after_ws) = extract_whitespace(&s);
+        if let Ok(s) = tag(ENTRY_SEPARATOR, &after_ws) {
+            let (_, s) = extract_whitespace(&s);
+            let s = tag(MAP_CLOSE, &s)?;
+
+            return Ok((s, Self { entries: vec![] }));
+        }
+
+        // the first entry decides whether this is a map at all, so it may not be skipped over
+        let (s, first) = Self::parse_entry(&s)?;
+        let mut entries = vec![first];
+        let mut s = s;
+
+        while let Ok(new_s) = tag(",", &extract_whitespace(&s).1) {
+            let (new_s, entry) = Self::parse_entry(&new_s)?;
+            entries.push(entry);
+            s = new_s;
+        }
+
+        let (_, s) = extract_whitespace(&s);
+        let s = tag(MAP_CLOSE, &s)?;
+
+        Ok((s, Self { entries }))
+    }
+}
+
+impl Eval for MapExpr {
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, crate::EvalError> {
+        let mut map = BTreeMap::new();
+
+        for (key, val) in &self.entries {
+            let key = match key.eval(env)? {
+                Val::Str(key) => key,
+                v => {
+                    return Err(EvalError::InvalidType {
+                        expected: "a string as map key".into(),
+                        received: v.get_type().into(),
+                    });
+                }
+            };
+
+            map.insert(key, val.eval(env)?);
+        }
+
+        Ok(Val::Map(map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{Env, Eval, EvalError, Parse, val::Val};
+
+    use super::MapExpr;
+
+    #[test]
+    fn parse_empty_map() {
+        assert_eq!(
+            MapExpr::parse("[ : ]"),
+            Ok(("".into(), MapExpr { entries: vec![] }))
+        )
+    }
+
+    #[test]
+    fn eval_map() {
+        let (_, map) = MapExpr::parse("[\"a\": 1, \"b\": [2]]").unwrap();
+
+        assert_eq!(
+            map.eval(&mut Env::new()),
+            Ok(Val::Map(BTreeMap::from([
+                ("a".into(), Val::Real(1.)),
+                ("b".into(), Val::List(vec![Val::Real(2.)]))
+            ])))
+        )
+    }
+
+    #[test]
+    fn eval_map_invalid_key() {
+        let (_, map) = MapExpr::parse("[1: 1]").unwrap();
+
+        assert_eq!(
+            map.eval(&mut Env::new()),
+            Err(EvalError::InvalidType {
+                expected: "a string as map key".into(),
+                received: "Real number".into()
+            })
+        )
+    }
+}
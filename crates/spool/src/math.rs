@@ -0,0 +1,377 @@
+use std::{f32::consts, sync::Arc};
+
+use crate::{
+    Env, ErrorVal,
+    binding::Identifier,
+    module::Module,
+    native::{IntoNativeResult, export_arg},
+    val::Val,
+};
+
+pub(crate) const MODULE_NAME: &str = "math";
+
+/// Registers each function under its own name. Arguments are converted with [`export_arg`], so
+/// that type errors name the parameter and function they're for.
+macro_rules! register {
+    ($env:ident, $($func:ident($($param:ident: $ty:ty),*)),* $(,)?) => {$(
+        $env.register_native(stringify!($func), [$(stringify!($param)),*].len(), |args| {
+            let mut args = args.iter();
+            $(
+                let $param: $ty =
+                    export_arg(stringify!($func), stringify!($param), args.next().unwrap())?;
+            )*
+            $func($($param),*).into_native_result()
+        });
+    )*};
+}
+
+/// Builds the `math` module, which is in scope everywhere without an import, unless something
+/// else is called `math`. Arguments outside of a function's domain raise a `MathError`.
+pub(crate) fn module() -> Arc<Module> {
+    let mut env = Env::new();
+
+    register!(
+        env,
+        sqrt(x: f32),
+        pow(base: f32, exp: f32),
+        abs(x: f32),
+        floor(x: f32),
+        ceil(x: f32),
+        round(x: f32),
+        min(a: f32, b: f32),
+        max(a: f32, b: f32),
+        clamp(x: f32, lo: f32, hi: f32),
+        sin(x: f32),
+        cos(x: f32),
+        tan(x: f32),
+        asin(x: f32),
+        acos(x: f32),
+        atan(x: f32),
+        atan2(y: f32, x: f32),
+        exp(x: f32),
+        ln(x: f32),
+        log(x: f32, base: f32),
+        log2(x: f32),
+        log10(x: f32),
+        sum(xs: Vec<f32>),
+        mean(xs: Vec<f32>),
+        median(xs: Vec<f32>),
+        stddev(xs: Vec<f32>),
+    );
+    env.store_binding(Identifier("pi".into()), Val::Real(consts::PI), true);
+    env.store_binding(Identifier("e".into()), Val::Real(consts::E), true);
+
+    let exports = env.store.lock().unwrap().keys().cloned().collect();
+    Arc::new(Module {
+        name: MODULE_NAME.into(),
+        store: env.store,
+        exports,
+    })
+}
+
+fn math_error(message: String) -> ErrorVal {
+    ErrorVal {
+        kind: "MathError".into(),
+        message,
+    }
+}
+
+/// Fails with `message` if `r` is not a number.
+fn defined(r: f32, message: impl FnOnce() -> String) -> Result<f32, ErrorVal> {
+    match r.is_nan() {
+        true => Err(math_error(message())),
+        false => Ok(r),
+    }
+}
+
+fn non_empty(func: &str, xs: &[f32]) -> Result<(), ErrorVal> {
+    match xs.is_empty() {
+        true => Err(math_error(format!(
+            "the {func} of an empty list is undefined"
+        ))),
+        false => Ok(()),
+    }
+}
+
+fn sqrt(x: f32) -> Result<f32, ErrorVal> {
+    defined(x.sqrt(), || format!("{x} has no real square root"))
+}
+
+fn pow(base: f32, exp: f32) -> Result<f32, ErrorVal> {
+    defined(base.powf(exp), || {
+        format!("{base} to the power of {exp} is not a real number")
+    })
+}
+
+fn abs(x: f32) -> f32 {
+    x.abs()
+}
+
+fn floor(x: f32) -> f32 {
+    x.floor()
+}
+
+fn ceil(x: f32) -> f32 {
+    x.ceil()
+}
+
+/// Rounds half-way cases away from zero.
+fn round(x: f32) -> f32 {
+    x.round()
+}
+
+fn min(a: f32, b: f32) -> f32 {
+    a.min(b)
+}
+
+fn max(a: f32, b: f32) -> f32 {
+    a.max(b)
+}
+
+fn clamp(x: f32, lo: f32, hi: f32) -> Result<f32, ErrorVal> {
+    if lo > hi {
+        return Err(math_error(format!(
+            "can't clamp to {lo}..={hi}, since {lo} is greater than {hi}"
+        )));
+    }
+
+    Ok(x.clamp(lo, hi))
+}
+
+fn sin(x: f32) -> f32 {
+    x.sin()
+}
+
+fn cos(x: f32) -> f32 {
+    x.cos()
+}
+
+fn tan(x: f32) -> f32 {
+    x.tan()
+}
+
+fn asin(x: f32) -> Result<f32, ErrorVal> {
+    defined(x.asin(), || format!("{x} is not between -1 and 1"))
+}
+
+fn acos(x: f32) -> Result<f32, ErrorVal> {
+    defined(x.acos(), || format!("{x} is not between -1 and 1"))
+}
+
+fn atan(x: f32) -> f32 {
+    x.atan()
+}
+
+fn atan2(y: f32, x: f32) -> f32 {
+    y.atan2(x)
+}
+
+fn exp(x: f32) -> f32 {
+    x.exp()
+}
+
+fn positive(x: f32) -> Result<f32, ErrorVal> {
+    match x > 0. {
+        true => Ok(x),
+        false => Err(math_error(format!("{x} has no real logarithm"))),
+    }
+}
+
+/// The natural logarithm.
+fn ln(x: f32) -> Result<f32, ErrorVal> {
+    Ok(positive(x)?.ln())
+}
+
+fn log(x: f32, base: f32) -> Result<f32, ErrorVal> {
+    if base <= 0. || base == 1. {
+        return Err(math_error(format!("{base} is not a valid logarithm base")));
+    }
+
+    Ok(positive(x)?.log(base))
+}
+
+fn log2(x: f32) -> Result<f32, ErrorVal> {
+    Ok(positive(x)?.log2())
+}
+
+fn log10(x: f32) -> Result<f32, ErrorVal> {
+    Ok(positive(x)?.log10())
+}
+
+fn sum(xs: Vec<f32>) -> f32 {
+    xs.iter().sum()
+}
+
+fn mean(xs: Vec<f32>) -> Result<f32, ErrorVal> {
+    non_empty("mean", &xs)?;
+    Ok(xs.iter().sum::<f32>() / xs.len() as f32)
+}
+
+/// The middle element, or the mean of the two middle elements of a list of even length.
+fn median(mut xs: Vec<f32>) -> Result<f32, ErrorVal> {
+    non_empty("median", &xs)?;
+    xs.sort_by(f32::total_cmp);
+
+    let mid = xs.len() / 2;
+    Ok(match xs.len() % 2 {
+        0 => (xs[mid - 1] + xs[mid]) / 2.,
+        _ => xs[mid],
+    })
+}
+
+/// The population standard deviation.
+fn stddev(xs: Vec<f32>) -> Result<f32, ErrorVal> {
+    let mean = mean(xs.clone())?;
+    let variance = xs.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / xs.len() as f32;
+
+    Ok(variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalError, Interpreter,
+        test_utils::{eval_src, reals},
+        val::Val,
+    };
+
+    /// Returns the kind and message of the error `src` raises.
+    fn math_error(src: &str) -> (String, String) {
+        match eval_src(src) {
+            Err(EvalError::Thrown(Val::Error(e))) => (e.kind, e.message),
+            out => panic!("expected an error, got {out:?}"),
+        }
+    }
+
+    #[test]
+    fn rounding_and_bounds() {
+        assert_eq!(
+            eval_src(
+                "[math.sqrt(16), math.pow(2, 10), math.abs(0 - 3), math.floor(1.5), \
+                 math.ceil(1.2), math.round(2.5), math.min(1, 2), math.max(1, 2), \
+                 math.clamp(5, 0, 3)]"
+            ),
+            Ok(reals(&[4., 1024., 3., 1., 2., 3., 1., 2., 3.]))
+        )
+    }
+
+    #[test]
+    fn trig_and_logs() {
+        assert_eq!(
+            eval_src(
+                "[math.cos(0), math.sin(math.pi / 2), math.atan2(0, 1), math.ln(1), \
+                 math.log2(8), math.log10(1000), math.round(math.log(81, 3)), math.exp(0)]"
+            ),
+            Ok(reals(&[1., 1., 0., 0., 3., 3., 4., 1.]))
+        )
+    }
+
+    #[test]
+    fn statistics() {
+        let xs = "[2, 4, 4, 4, 5, 5, 7, 9]";
+
+        assert_eq!(
+            eval_src(&format!(
+                "bind xs = {xs}; [math.sum(xs), math.mean(xs), math.median(xs), math.stddev(xs), \
+                 math.median([3, 1, 2]), math.sum([])]"
+            )),
+            Ok(reals(&[40., 5., 4.5, 2., 2., 0.]))
+        )
+    }
+
+    #[test]
+    fn domain_errors() {
+        assert_eq!(
+            math_error("math.sqrt(0 - 4)"),
+            ("MathError".into(), "-4 has no real square root".into())
+        );
+        assert_eq!(
+            math_error("math.ln(0)"),
+            ("MathError".into(), "0 has no real logarithm".into())
+        );
+        assert_eq!(
+            math_error("math.log(8, 1)"),
+            ("MathError".into(), "1 is not a valid logarithm base".into())
+        );
+        assert_eq!(
+            math_error("math.acos(2)"),
+            ("MathError".into(), "2 is not between -1 and 1".into())
+        );
+        assert_eq!(
+            math_error("math.pow(0 - 8, 0.5)"),
+            (
+                "MathError".into(),
+                "-8 to the power of 0.5 is not a real number".into()
+            )
+        );
+        assert_eq!(
+            math_error("math.clamp(1, 3, 2)"),
+            (
+                "MathError".into(),
+                "can't clamp to 3..=2, since 3 is greater than 2".into()
+            )
+        );
+        assert_eq!(
+            math_error("math.median([])"),
+            (
+                "MathError".into(),
+                "the median of an empty list is undefined".into()
+            )
+        );
+        assert_eq!(
+            eval_src("try { math.sqrt(0 - 1) } catch e { e.kind }"),
+            Ok(Val::Str("MathError".into()))
+        )
+    }
+
+    #[test]
+    fn typed_arguments() {
+        assert_eq!(
+            eval_src("math.sqrt(\"4\")"),
+            Err(EvalError::InvalidType {
+                expected: "a real number for argument `x` of sqrt".into(),
+                received: "String".into()
+            })
+        );
+        assert_eq!(
+            eval_src("math.mean([1, true])"),
+            Err(EvalError::InvalidType {
+                expected: "a real number for argument `xs` of mean".into(),
+                received: "Boolean".into()
+            })
+        );
+        assert_eq!(
+            eval_src("math.pow(2)"),
+            Err(EvalError::TooFewArguments {
+                expected: 2,
+                received: 1
+            })
+        )
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(
+            eval_src("[math.pi, math.e]"),
+            Ok(reals(&[std::f32::consts::PI, std::f32::consts::E]))
+        );
+        assert_eq!(
+            eval_src("func area(r) => math.pi * r * r; area(1)"),
+            Ok(Val::Real(std::f32::consts::PI))
+        )
+    }
+
+    #[test]
+    fn built_per_interpreter() {
+        let (mut a, mut b) = (Interpreter::new(), Interpreter::new());
+        let math = a.eval_str("math");
+
+        assert_eq!(a.eval_str("func f() => math; f()"), math);
+        assert_ne!(b.eval_str("math"), math)
+    }
+
+    #[test]
+    fn math_can_be_shadowed() {
+        assert_eq!(eval_src("bind math = 1; math"), Ok(Val::Real(1.)))
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    Env, Eval, EvalError, Parse, ParseError,
+    binding::Identifier,
+    expr::Expr,
+    fn_call::{Arg, CallArgs, parse_args},
+    iter::{ITER_METHODS, call_iter_method},
+    module::Module,
+    val::Val,
+};
+
+const MEMBER_TOKEN: &str = ".";
+const OPTIONAL_MEMBER_TOKEN: &str = "?.";
+
+/// Accesses a field, or calls a method, such as `p.x` or `xs.map(f)`. Methods are looked up on
+/// the struct first, and fall back to the built-in iterator methods. Fields of maps are their
+/// entries, so `m.key` is the same as `m["key"]`.
+///
+/// The optional form `a?.b` evaluates to `nil` if `a` is `nil`, or if it has no field `b`. That
+/// `nil` short-circuits the rest of the chain, so `a?.b.c` is `nil` too rather than an error.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MemberExpr {
+    pub target: Expr,
+    pub member: Identifier,
+    pub args: Option<Vec<Arg>>,
+    pub optional: bool,
+}
+
+impl MemberExpr {
+    /// Parses `.member` or `.member(args)`, or their optional forms starting with `?.`,
+    /// following the already parsed target. Like indexing, the dot must directly follow the
+    /// target, and `..` is left alone for ranges.
+    pub(crate) fn parse_member(
+        s: &str,
+    ) -> crate::ParseOutput<(Identifier, Option<Vec<Arg>>, bool)> {
+        let (s, optional) = match s.strip_prefix(OPTIONAL_MEMBER_TOKEN) {
+            Some(rest) => (rest, true),
+            None => (parse_dot(s)?, false),
+        };
+        let (s, member) = Identifier::parse(s)?;
+
+        match parse_args(&s) {
+            Ok((s, args)) => Ok((s, (member, Some(args), optional))),
+            Err(_) => Ok((s, (member, None, optional))),
+        }
+    }
+}
+
+/// Strips a single `.` from the start of `s`.
+pub(crate) fn parse_dot(s: &str) -> Result<&str, ParseError> {
+    match s.strip_prefix(MEMBER_TOKEN) {
+        Some(rest) if !rest.starts_with(MEMBER_TOKEN) => Ok(rest),
+        _ => Err(ParseError::SequenceNotFound {
+            expected: MEMBER_TOKEN.into(),
+            received: s.into(),
+        }),
+    }
+}
+
+impl MemberExpr {
+    /// Accesses a member of a module, calling it if there are arguments.
+    fn eval_module_member(&self, module: &Module, env: &mut Env) -> Result<Option<Val>, EvalError> {
+        let member = match module.get(&self.member) {
+            Ok(member) => member,
+            Err(EvalError::UnknownMember { .. }) if self.optional => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        match self.args {
+            Some(ref args) => member.call(CallArgs::eval(args, env)?).map(Some),
+            None => member.into_val().map(Some),
+        }
+    }
+}
+
+/// Returns the name of `val`'s type as reported in errors: the struct's name for instances.
+pub(crate) fn type_name(val: &Val) -> String {
+    match val {
+        Val::Struct(inst) => inst.lock().unwrap().def.id.0.clone(),
+        v => v.get_type().into(),
+    }
+}
+
+impl Eval for MemberExpr {
+    fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        Ok(self.eval_chain(env)?.unwrap_or(Val::Nil))
+    }
+}
+
+impl MemberExpr {
+    /// Evaluates the chain of member accesses ending in `self`, or returns `None` once an
+    /// optional access along the way comes up empty.
+    fn eval_chain(&self, env: &mut Env) -> Result<Option<Val>, EvalError> {
+        let target = match self.target.as_member() {
+            Some(member) => match member.eval_chain(env)? {
+                Some(target) => target,
+                None => return Ok(None),
+            },
+            None => self.target.eval(env)?,
+        };
+
+        if self.optional && target == Val::Nil {
+            return Ok(None);
+        }
+
+        let unknown = || EvalError::UnknownMember {
+            ty: type_name(&target),
+            member: self.member.clone(),
+        };
+
+        if let Val::Module(ref module) = target {
+            return self.eval_module_member(module, env);
+        }
+
+        let Some(ref args) = self.args else {
+            let field = match (&target, self.member.0.as_str()) {
+                (Val::Struct(inst), _) => inst.lock().unwrap().fields.get(&self.member).cloned(),
+                (Val::Map(map), key) => map.get(key).cloned(),
+                (Val::Error(e), "kind") => Some(Val::Str(e.kind.clone())),
+                (Val::Error(e), "message") => Some(Val::Str(e.message.clone())),
+                _ => None,
+            };
+
+            return match (field, &target) {
+                (Some(v), _) => Ok(Some(v)),
+                (None, _) if self.optional => Ok(None),
+                (None, Val::Map(_)) => Err(EvalError::KeyNotFound(self.member.0.clone())),
+                (None, _) => Err(unknown()),
+            };
+        };
+
+        let mut args = CallArgs::eval(args, env)?;
+
+        if let Val::Struct(ref inst) = target {
+            let method = inst.lock().unwrap().method(&self.member);
+            if let Some(method) = method {
+                args.positional.insert(0, target.clone());
+                return method.call(args).map(Some);
+            }
+        }
+
+        if !ITER_METHODS.contains(&self.member.0.as_str()) {
+            return Err(unknown());
+        }
+
+        call_iter_method(target, &self.member, args).map(Some)
+    }
+}
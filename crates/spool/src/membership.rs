@@ -0,0 +1,92 @@
+use crate::{
+    Eval, EvalError, ParseError,
+    expr::Expr,
+    loops::IN_KW,
+    range::RangeExpr,
+    utils::{extract_whitespace, starts_with_keyword},
+    val::Val,
+};
+
+/// A membership test such as `x in 1..10`, `"key" in map` or `"ell" in "hello"`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct InExpr {
+    pub lhs: Expr,
+    pub rhs: Expr,
+}
+
+impl InExpr {
+    /// Parses `in rhs`, following the already parsed left hand side.
+    pub(crate) fn parse_rhs(s: &str) -> crate::ParseOutput<Expr> {
+        let (_, s) = extract_whitespace(s);
+
+        if !starts_with_keyword(IN_KW, &s) {
+            return Err(ParseError::SequenceNotFound {
+                expected: IN_KW.into(),
+                received: s,
+            });
+        }
+
+        RangeExpr::parse_or_operand(&s[IN_KW.len()..])
+    }
+}
+
+impl Eval for InExpr {
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, EvalError> {
+        let lhs = self.lhs.eval(env)?;
+        let rhs = self.rhs.eval(env)?;
+
+        let contained = match (rhs, lhs) {
+            (Val::Range(range), Val::Real(x)) => range.contains(x),
+            (Val::List(items), x) => items.contains(&x),
+            (Val::Map(map), Val::Str(key)) => map.contains_key(&key),
+            (Val::Str(s), Val::Str(sub)) => s.contains(&sub),
+            (Val::Range(_) | Val::Map(_) | Val::Str(_), x) => {
+                return Err(EvalError::InvalidType {
+                    expected: "a value of the collection's element type".into(),
+                    received: x.get_type().into(),
+                });
+            }
+            (v, _) => {
+                return Err(EvalError::InvalidType {
+                    expected: "a range, list, map or string".into(),
+                    received: v.get_type().into(),
+                });
+            }
+        };
+
+        Ok(Val::Bool(contained))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvalError, test_utils::eval_src, val::Val};
+
+    #[test]
+    fn in_range() {
+        assert_eq!(eval_src("5 in 1..10"), Ok(Val::Bool(true)));
+        assert_eq!(eval_src("10 in 1..10"), Ok(Val::Bool(false)));
+        assert_eq!(eval_src("10 in 1..=10"), Ok(Val::Bool(true)));
+        assert_eq!(eval_src("3 in 0..10 step 2"), Ok(Val::Bool(false)));
+        assert_eq!(eval_src("3 in ..5"), Ok(Val::Bool(true)))
+    }
+
+    #[test]
+    fn in_collections() {
+        assert_eq!(eval_src("2 in [1, 2]"), Ok(Val::Bool(true)));
+        assert_eq!(eval_src("\"b\" in [\"a\": 1]"), Ok(Val::Bool(false)));
+        assert_eq!(eval_src("\"ell\" in \"hello\""), Ok(Val::Bool(true)));
+        assert_eq!(eval_src("!{ \"x\" in \"hello\" }"), Ok(Val::Bool(true)))
+    }
+
+    #[test]
+    fn in_non_collection() {
+        assert_eq!(
+            eval_src("1 in 1"),
+            Err(EvalError::InvalidType {
+                expected: "a range, list, map or string".into(),
+                received: "Real number".into()
+            })
+        )
+    }
+}
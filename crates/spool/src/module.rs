@@ -0,0 +1,627 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    Env, Eval, EvalError, Parse, ParseError, Parsed,
+    binding::Identifier,
+    env::{Store, Storeable},
+    math,
+    member::parse_dot,
+    stmt::Stmt,
+    utils::{extract_string, extract_whitespace, tag},
+    val::Val,
+};
+
+pub(crate) const IMPORT_KW: &str = "import";
+pub(crate) const EXPORT_KW: &str = "pub";
+const MODULE_EXT: &str = "spool";
+
+/// Modules that were already loaded, the ones that are being loaded, and the packages imports
+/// can refer to by name, which are shared by every module of a program.
+#[derive(Debug, Default)]
+pub struct Loader {
+    cache: HashMap<PathBuf, Arc<Module>>,
+    loading: Vec<PathBuf>,
+    /// The entry files of packages, by package name.
+    packages: HashMap<String, PathBuf>,
+    /// The built-in `math` module, which is built once the program first uses it.
+    math: Option<Arc<Module>>,
+    /// Whether the program may use the built-in file functions, see [`Env::allow_file_access`].
+    file_access: bool,
+}
+
+/// What a module needs to resolve its imports: the file it was read from, and the loader shared
+/// with the rest of the program.
+#[derive(Debug, Default)]
+pub struct ModuleCtx {
+    pub(crate) file: Option<PathBuf>,
+    pub(crate) loader: Arc<Mutex<Loader>>,
+}
+
+impl ModuleCtx {
+    /// Evaluates `parsed`, the contents of `self.file`, in `env`. Meanwhile the file counts as
+    /// being loaded, so that a module importing it back is reported as a cycle instead of
+    /// evaluating it a second time.
+    pub(crate) fn eval_file(&self, parsed: &Parsed, env: &mut Env) -> Result<Val, EvalError> {
+        let file = self.file.as_deref().map(canonical);
+
+        if let Some(ref file) = file {
+            self.loader.lock().unwrap().loading.push(file.clone());
+        }
+        let out = parsed.eval(env);
+        if file.is_some() {
+            self.loader.lock().unwrap().loading.pop();
+        }
+
+        out
+    }
+
+    /// Returns the `math` module of this program.
+    pub(crate) fn math(&self) -> Arc<Module> {
+        let mut loader = self.loader.lock().unwrap();
+        loader.math.get_or_insert_with(math::module).clone()
+    }
+
+    /// Whether this program may read and write files.
+    pub(crate) fn file_access(&self) -> bool {
+        self.loader.lock().unwrap().file_access
+    }
+
+    /// The directory imports are resolved relative to.
+    fn base_dir(&self) -> PathBuf {
+        match self.file.as_ref().and_then(|f| f.parent()) {
+            Some(dir) => dir.into(),
+            None => PathBuf::from("."),
+        }
+    }
+}
+
+impl Env {
+    /// Creates the top-level scope of the program read from `path`. Its imports are resolved
+    /// relative to the directory `path` is in, and importing `path` itself is an import cycle.
+    pub fn for_file(path: impl Into<PathBuf>) -> Self {
+        let file = path.into();
+        let loader = Loader {
+            loading: vec![canonical(&file)],
+            ..Loader::default()
+        };
+
+        Self {
+            module: Some(Arc::new(ModuleCtx {
+                file: Some(file),
+                loader: Arc::new(Mutex::new(loader)),
+            })),
+            ..Self::default()
+        }
+    }
+
+    /// Makes the package `name`, whose entry file is at `entry`, importable from every module of
+    /// the program. `import name` imports the entry file, and `import name.a.b` imports
+    /// `a/b.spool` next to it. Modules next to the importer take precedence.
+    pub fn add_package(&mut self, name: impl Into<String>, entry: impl Into<PathBuf>) {
+        self.module_ctx()
+            .loader
+            .lock()
+            .unwrap()
+            .packages
+            .insert(name.into(), entry.into());
+    }
+
+    /// Lets every module of the program read and write files with the built-in `readFile` and
+    /// `writeFile` functions. Otherwise scripts can only read the modules they import, so only allow
+    /// it for trusted ones.
+    pub fn allow_file_access(&mut self) {
+        self.module_ctx().loader.lock().unwrap().file_access = true;
+    }
+}
+
+/// An evaluated module. Modules are evaluated once, into their own scope, no matter how often
+/// they're imported. Only the members declared with `pub` can be accessed from outside.
+#[derive(Debug)]
+pub struct Module {
+    pub(crate) name: String,
+    pub(crate) store: Store,
+    pub(crate) exports: HashSet<Identifier>,
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Module {
+    /// Looks up the exported member `id`.
+    pub(crate) fn get(&self, id: &Identifier) -> Result<Storeable, EvalError> {
+        let member = self.store.lock().unwrap().get(id).cloned().ok_or_else(|| {
+            EvalError::UnknownMember {
+                ty: format!("module {}", self.name),
+                member: id.clone(),
+            }
+        })?;
+
+        if !self.exports.contains(id) {
+            return Err(EvalError::PrivateImport {
+                module: self.name.clone(),
+                member: id.clone(),
+            });
+        }
+
+        Ok(member)
+    }
+
+    /// Loads the module at `path`, or returns it from the cache if it was loaded before.
+    fn load(ctx: &ModuleCtx, path: PathBuf) -> Result<Arc<Self>, EvalError> {
+        {
+            let loader = ctx.loader.lock().unwrap();
+
+            if let Some(module) = loader.cache.get(&path) {
+                return Ok(module.clone());
+            }
+
+            if let Some(idx) = loader.loading.iter().position(|p| *p == path) {
+                let cycle = loader.loading[idx..].iter().chain([&path]);
+                return Err(EvalError::ImportCycle(
+                    cycle.map(|p| p.display().to_string()).collect(),
+                ));
+            }
+        }
+
+        let display = path.display().to_string();
+        let src = std::fs::read_to_string(&path)
+            .map_err(|_| EvalError::ModuleNotFound(display.clone()))?;
+        let (_, parsed) = Parsed::parse(&src).map_err(|error| EvalError::ModuleParse {
+            module: display,
+            error,
+        })?;
+
+        let module_ctx = Arc::new(ModuleCtx {
+            file: Some(path.clone()),
+            loader: ctx.loader.clone(),
+        });
+        let mut env = Env {
+            module: Some(module_ctx.clone()),
+            ..Env::default()
+        };
+        module_ctx.eval_file(&parsed, &mut env)?;
+
+        let module = Arc::new(Self {
+            name: module_name(&path),
+            store: env.store,
+            exports: env.exports,
+        });
+
+        ctx.loader
+            .lock()
+            .unwrap()
+            .cache
+            .insert(path, module.clone());
+        Ok(module)
+    }
+}
+
+/// Returns the path imports of the file at `path` are identified by, which is `path` itself if
+/// it can't be resolved.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.into())
+}
+
+fn module_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// What an import refers to: either a path to a file, or a dotted module path such as `lib.math`,
+/// which stands for `lib/math.spool`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ImportPath {
+    File(String),
+    Dotted(Vec<Identifier>),
+}
+
+impl ImportPath {
+    fn relative(&self) -> PathBuf {
+        match self {
+            Self::File(path) => path.into(),
+            Self::Dotted(segments) => {
+                let mut path: PathBuf = segments.iter().map(|s| &s.0).collect();
+                path.set_extension(MODULE_EXT);
+                path
+            }
+        }
+    }
+
+    /// The package a dotted path refers to, if any, and the path of the module inside it.
+    fn package_path(&self, ctx: &ModuleCtx) -> Option<PathBuf> {
+        let Self::Dotted(segments) = self else {
+            return None;
+        };
+
+        let loader = ctx.loader.lock().unwrap();
+        let entry = loader.packages.get(&segments[0].0)?;
+
+        if segments.len() == 1 {
+            return Some(entry.clone());
+        }
+
+        let mut path = entry.parent().unwrap_or(Path::new(".")).to_path_buf();
+        path.extend(segments[1..].iter().map(|s| &s.0));
+        path.set_extension(MODULE_EXT);
+        Some(path)
+    }
+
+    /// Resolves `self` relative to the importing module, falling back to the packages.
+    fn resolve(&self, ctx: &ModuleCtx) -> Result<PathBuf, EvalError> {
+        let relative = self.relative();
+
+        std::iter::once(ctx.base_dir().join(&relative))
+            .chain(self.package_path(ctx))
+            .find_map(|path| path.canonicalize().ok())
+            .ok_or_else(|| EvalError::ModuleNotFound(relative.display().to_string()))
+    }
+}
+
+/// An import of another module. `import "lib.spool"` and `import lib` bind the module to `lib`,
+/// so that its members are accessed as `lib.name`, while `import lib.{a, b}` binds only the
+/// members `a` and `b`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Import {
+    pub path: ImportPath,
+    pub names: Option<Vec<Identifier>>,
+}
+
+impl Import {
+    /// The name a module imported by its path is bound to, which is its file name.
+    fn file_binding(module: &Module) -> Result<Identifier, EvalError> {
+        Identifier::parse(&module.name)
+            .ok()
+            .filter(|(rest, _)| rest.is_empty())
+            .map(|(_, id)| id)
+            .ok_or_else(|| EvalError::InvalidType {
+                expected: "a module name that is a valid identifier".into(),
+                received: module.name.clone(),
+            })
+    }
+
+    fn parse_names(s: &str) -> crate::ParseOutput<Vec<Identifier>> {
+        let mut s = tag("{", s)?;
+        let mut names = vec![];
+
+        while let Ok((rest, name)) = Identifier::parse(&s) {
+            names.push(name);
+            s = match tag(",", &extract_whitespace(&rest).1) {
+                Ok(rest) => rest,
+                Err(_) => rest,
+            };
+        }
+
+        let (_, s) = extract_whitespace(&s);
+        let s = tag("}", &s)?;
+
+        Ok((s, names))
+    }
+}
+
+impl Parse for Import {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(IMPORT_KW, &s)?;
+        let (_, s) = extract_whitespace(&s);
+
+        let (mut s, mut path) = match extract_string(&s) {
+            Ok((path, rest)) => (rest, ImportPath::File(path)),
+            Err(_) => {
+                let (s, first) = Identifier::parse(&s)?;
+                (s, ImportPath::Dotted(vec![first]))
+            }
+        };
+
+        let mut names = None;
+        while let Ok(rest) = parse_dot(&s) {
+            match (Identifier::parse(rest), &mut path) {
+                (Ok((rest, segment)), ImportPath::Dotted(segments)) => {
+                    segments.push(segment);
+                    s = rest;
+                }
+                _ => {
+                    let (rest, list) = Self::parse_names(rest)?;
+                    names = Some(list);
+                    s = rest;
+                    break;
+                }
+            }
+        }
+
+        Ok((s, Self { path, names }))
+    }
+}
+
+impl Eval for Import {
+    fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        let ctx = env.module_ctx();
+        let module = Module::load(&ctx, self.path.resolve(&ctx)?)?;
+
+        let Some(ref names) = self.names else {
+            let id = match self.path {
+                // the last segment, which differs from the file name for packages
+                ImportPath::Dotted(ref segments) => segments[segments.len() - 1].clone(),
+                ImportPath::File(_) => Self::file_binding(&module)?,
+            };
+
+            env.store_binding(id, Val::Module(module), true);
+            return Ok(Val::Unit);
+        };
+
+        for name in names {
+            let member = module.get(name)?;
+            env.store.lock().unwrap().insert(name.clone(), member);
+        }
+
+        Ok(Val::Unit)
+    }
+}
+
+/// A declaration marked with `pub`, as in `pub func area(r) => ...`, which the module exports.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Export {
+    pub stmt: Box<Stmt>,
+}
+
+impl Export {
+    /// The name the exported statement declares.
+    fn id(&self) -> &Identifier {
+        match *self.stmt {
+            Stmt::Binding(ref b) => &b.ident,
+            Stmt::Func(ref f) => &f.id,
+            Stmt::Struct(ref s) => &s.id,
+            // rejected by the parser
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Parse for Export {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(EXPORT_KW, &s)?;
+        let (_, decl) = extract_whitespace(&s);
+
+        match Stmt::parse(&decl)? {
+            (s, stmt @ (Stmt::Binding(_) | Stmt::Func(_) | Stmt::Struct(_))) => Ok((
+                s,
+                Self {
+                    stmt: Box::new(stmt),
+                },
+            )),
+            _ => Err(ParseError::SequenceNotFound {
+                expected: "a `bind`, `func` or `struct` declaration".into(),
+                received: decl,
+            }),
+        }
+    }
+}
+
+impl Eval for Export {
+    fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        // only the top-level scope of a module, which has neither a parent nor globals to fall
+        // back to, has its exports read
+        if env.parent.is_some() || env.globals.is_some() {
+            return Err(EvalError::NestedExport(self.id().clone()));
+        }
+
+        let out = self.stmt.eval(env)?;
+        env.exports.insert(self.id().clone());
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Env, EvalError, Parse, ParseError, Parsed,
+        stmt::Stmt,
+        test_utils::{Fixture, eval_src},
+        val::Val,
+    };
+
+    use super::{Import, ImportPath};
+
+    /// Writes `files` to a fresh directory, and evaluates the one called `main.spool`.
+    fn eval_files(files: &[(&str, &str)]) -> Result<Val, EvalError> {
+        eval_main(&Fixture::new(files))
+    }
+
+    fn eval_main(files: &Fixture) -> Result<Val, EvalError> {
+        let main = files.path("main.spool");
+        let (_, parsed) = Parsed::parse(&std::fs::read_to_string(&main).unwrap()).unwrap();
+        parsed.eval(&mut Env::for_file(main))
+    }
+
+    #[test]
+    fn parse_imports() {
+        assert_eq!(
+            Import::parse("import \"lib/math.spool\""),
+            Ok((
+                "".into(),
+                Import {
+                    path: ImportPath::File("lib/math.spool".into()),
+                    names: None
+                }
+            ))
+        );
+        assert_eq!(
+            Import::parse("import lib.math.{sqrt, pi}"),
+            Ok((
+                "".into(),
+                Import {
+                    path: ImportPath::Dotted(vec!["lib".into(), "math".into()]),
+                    names: Some(vec!["sqrt".into(), "pi".into()])
+                }
+            ))
+        )
+    }
+
+    #[test]
+    fn import_namespace() {
+        assert_eq!(
+            eval_files(&[
+                (
+                    "main.spool",
+                    "import \"lib/util.spool\"; util.double(util.base)"
+                ),
+                (
+                    "lib/util.spool",
+                    "pub bind base = 2; func twice(x) => x * 2; pub func double(x) => twice(x)"
+                ),
+            ]),
+            Ok(Val::Real(4.))
+        )
+    }
+
+    #[test]
+    fn import_names_relative_to_importer() {
+        assert_eq!(
+            eval_files(&[
+                (
+                    "main.spool",
+                    "import lib.shapes.{Point, origin}; Point(origin, 1).y"
+                ),
+                (
+                    "lib/shapes.spool",
+                    "import \"consts.spool\"; pub bind origin = consts.zero; pub struct Point { x, y }"
+                ),
+                ("lib/consts.spool", "pub bind zero = 0"),
+            ]),
+            Ok(Val::Real(1.))
+        )
+    }
+
+    #[test]
+    fn modules_are_evaluated_once() {
+        assert_eq!(
+            eval_files(&[
+                ("main.spool", "import a; import b; [a.count(), b.count()]"),
+                ("a.spool", "import c; pub func count() => c.count()"),
+                ("b.spool", "import c; pub func count() => c.count()"),
+                (
+                    "c.spool",
+                    "struct Counter { n = 0; func next(self) => { self.n += 1; self.n } }; bind calls = Counter(); pub func count() => calls.next()"
+                ),
+            ]),
+            Ok(Val::List(vec![Val::Real(1.), Val::Real(2.)]))
+        )
+    }
+
+    #[test]
+    fn private_members() {
+        let files = |main| {
+            [
+                ("main.spool", main),
+                ("lib.spool", "bind secret = 1; pub func reveal() => secret"),
+            ]
+        };
+        let private = Err(EvalError::PrivateImport {
+            module: "lib".into(),
+            member: "secret".into(),
+        });
+
+        assert_eq!(
+            eval_files(&files("import lib.{reveal}; reveal()")),
+            Ok(Val::Real(1.))
+        );
+        assert_eq!(eval_files(&files("import lib.{secret}")), private);
+        assert_eq!(eval_files(&files("import lib; lib.secret")), private)
+    }
+
+    #[test]
+    fn nested_export() {
+        assert_eq!(
+            eval_src("{ pub bind x = 1 }"),
+            Err(EvalError::NestedExport("x".into()))
+        );
+        assert_eq!(
+            eval_src("func f() => { pub func g() => 1 }; f()"),
+            Err(EvalError::NestedExport("g".into()))
+        );
+        assert_eq!(eval_src("pub bind x = 1; x"), Ok(Val::Real(1.)))
+    }
+
+    #[test]
+    fn parse_export() {
+        assert_eq!(
+            Stmt::parse("pub import lib"),
+            Err(ParseError::SequenceNotFound {
+                expected: "a `bind`, `func` or `struct` declaration".into(),
+                received: "import lib".into()
+            })
+        );
+        assert!(matches!(
+            Stmt::parse("pub func f() => 1"),
+            Ok((_, Stmt::Export(_)))
+        ))
+    }
+
+    #[test]
+    fn import_package() {
+        let files = Fixture::new(&[
+            (
+                "geom/src/lib.spool",
+                "import shapes; pub bind unit = shapes.square(1)",
+            ),
+            ("geom/src/shapes.spool", "pub func square(n) => n * n"),
+        ]);
+
+        let mut env = Env::for_file(files.path("main.spool"));
+        env.add_package("geom", files.path("geom/src/lib.spool"));
+
+        let (_, parsed) =
+            Parsed::parse("import geom; import geom.shapes; geom.unit + shapes.square(3)").unwrap();
+        assert_eq!(parsed.eval(&mut env), Ok(Val::Real(10.)))
+    }
+
+    #[test]
+    fn import_cycle() {
+        let files = Fixture::new(&[
+            ("main.spool", "import a"),
+            ("a.spool", "import b"),
+            ("b.spool", "import a"),
+        ]);
+        let path = |name| files.path(name).display().to_string();
+
+        assert_eq!(
+            eval_main(&files),
+            Err(EvalError::ImportCycle(vec![
+                path("a.spool"),
+                path("b.spool"),
+                path("a.spool"),
+            ]))
+        )
+    }
+
+    #[test]
+    fn import_cycle_through_entry() {
+        let files = Fixture::new(&[("main.spool", "import a"), ("a.spool", "import main")]);
+        let path = |name| files.path(name).display().to_string();
+
+        assert_eq!(
+            eval_main(&files),
+            Err(EvalError::ImportCycle(vec![
+                path("main.spool"),
+                path("a.spool"),
+                path("main.spool"),
+            ]))
+        )
+    }
+
+    #[test]
+    fn import_missing() {
+        assert_eq!(
+            eval_files(&[("main.spool", "import nope")]),
+            Err(EvalError::ModuleNotFound("nope.spool".into()))
+        )
+    }
+}
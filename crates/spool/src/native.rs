@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use crate::{
+    Env, EvalError, FromVal, IntoVal, binding::Identifier, env::Storeable, error::ErrorVal,
+    fn_call::CallArgs, val::Val,
+};
+
+type NativeFn = dyn Fn(&[Val]) -> Result<Val, EvalError> + Send + Sync;
+
+/// A function implemented by the host application, which takes a fixed number of positional
+/// arguments.
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub(crate) arity: usize,
+    func: Arc<NativeFn>,
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.func, &other.func)
+    }
+}
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NativeFunction({})", self.arity)
+    }
+}
+
+impl NativeFunction {
+    pub(crate) fn call(&self, args: CallArgs) -> Result<Val, EvalError> {
+        if let Some((name, _)) = args.named.into_iter().next() {
+            return Err(EvalError::UnknownArgument(name));
+        }
+
+        let received = args.positional.len();
+        if received > self.arity {
+            return Err(EvalError::TooManyArguments {
+                expected: self.arity,
+                received,
+            });
+        }
+
+        if received < self.arity {
+            return Err(EvalError::TooFewArguments {
+                expected: self.arity,
+                received,
+            });
+        }
+
+        (self.func)(&args.positional)
+    }
+}
+
+/// A Rust function exported to scripts by `#[spool::export]`, which is available with the
+/// `derive` feature and generates an implementation for a struct of the same name as the function.
+pub trait NativeExport: 'static {
+    /// The name scripts call the function by.
+    const NAME: &'static str;
+    const ARITY: usize;
+
+    /// Converts `args`, of which there are exactly [`Self::ARITY`], and calls the function.
+    fn call(args: &[Val]) -> Result<Val, EvalError>;
+}
+
+/// The return value of an exported function: either a value, or a `Result` whose error converts
+/// to an [`EvalError`].
+pub trait IntoNativeResult {
+    fn into_native_result(self) -> Result<Val, EvalError>;
+}
+
+impl<T: IntoVal> IntoNativeResult for T {
+    fn into_native_result(self) -> Result<Val, EvalError> {
+        Ok(self.into_val())
+    }
+}
+
+impl<T: IntoVal, E: Into<EvalError>> IntoNativeResult for Result<T, E> {
+    fn into_native_result(self) -> Result<Val, EvalError> {
+        self.map(IntoVal::into_val).map_err(Into::into)
+    }
+}
+
+/// Converts the argument for the parameter `param` of the exported function `func`, naming
+/// both if it has the wrong type.
+pub fn export_arg<T: FromVal>(func: &str, param: &str, val: &Val) -> Result<T, EvalError> {
+    T::from_val(val.clone()).map_err(|e| match e {
+        EvalError::InvalidType { expected, received } => EvalError::InvalidType {
+            expected: format!("{expected} for argument `{param}` of {func}"),
+            received,
+        },
+        e => e,
+    })
+}
+
+/// Lets exported functions fail with an error that scripts can catch and inspect.
+impl From<ErrorVal> for EvalError {
+    fn from(value: ErrorVal) -> Self {
+        Self::Thrown(Val::Error(value))
+    }
+}
+
+impl Env {
+    /// Registers the function exported as `F` by `#[spool::export]`.
+    pub fn register_export<F: NativeExport>(&mut self) {
+        self.register_native(F::NAME, F::ARITY, F::call);
+    }
+
+    /// Makes the host function `func` callable as `name`, with exactly `arity` positional
+    /// arguments. Errors returned by `func` are raised like any other error, so scripts can
+    /// catch them with `try`.
+    pub fn register_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        func: impl Fn(&[Val]) -> Result<Val, EvalError> + Send + Sync + 'static,
+    ) {
+        self.store.lock().unwrap().insert(
+            Identifier(name.into()),
+            Storeable::Native(NativeFunction {
+                arity,
+                func: Arc::new(func),
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Env, EvalError, test_utils::eval_in, val::Val};
+
+    fn eval_with_natives(src: &str) -> Result<Val, EvalError> {
+        let mut env = Env::new();
+        env.register_native("add", 2, |args| match args {
+            [Val::Real(a), Val::Real(b)] => Ok(Val::Real(a + b)),
+            _ => Err(EvalError::InvalidType {
+                expected: "two real numbers".into(),
+                received: format!("{} and {}", args[0].get_type(), args[1].get_type()),
+            }),
+        });
+        env.register_native("parseReal", 1, |_| Ok(Val::Nil));
+
+        eval_in(src, &mut env)
+    }
+
+    #[test]
+    fn call_native() {
+        assert_eq!(eval_with_natives("add(1, 2)"), Ok(Val::Real(3.)));
+        assert_eq!(
+            eval_with_natives("func twice(x) => add(x, x); twice(4)"),
+            Ok(Val::Real(8.))
+        )
+    }
+
+    #[test]
+    fn natives_shadow_builtins() {
+        assert_eq!(eval_with_natives("parseReal(\"1\")"), Ok(Val::Nil))
+    }
+
+    #[test]
+    fn native_arity() {
+        assert_eq!(
+            eval_with_natives("add(1)"),
+            Err(EvalError::TooFewArguments {
+                expected: 2,
+                received: 1
+            })
+        );
+        assert_eq!(
+            eval_with_natives("add(1, 2, 3)"),
+            Err(EvalError::TooManyArguments {
+                expected: 2,
+                received: 3
+            })
+        );
+        assert_eq!(
+            eval_with_natives("add(1, b: 2)"),
+            Err(EvalError::UnknownArgument("b".into()))
+        )
+    }
+
+    #[test]
+    fn native_errors_are_catchable() {
+        assert_eq!(
+            eval_with_natives("try { add(1, \"2\") } catch e { e.message }"),
+            Ok(Val::Str(
+                "expected two real numbers, received Real number and String".into()
+            ))
+        )
+    }
+}
@@ -0,0 +1,321 @@
+use crate::{
+    Eval, EvalError, Parse, ParseError,
+    expr::Expr,
+    index::expect_index,
+    utils::{extract_whitespace, starts_with_keyword},
+    val::Val,
+};
+
+pub(crate) const RANGE_TOKEN: &str = "..";
+const INCLUSIVE_TOKEN: &str = "=";
+pub(crate) const STEP_KW: &str = "step";
+
+/// A range of real numbers such as `0..10`, `1..=5 step 2` or `..3`. Ranges are lazy: they only
+/// store their bounds, and produce their elements one at a time when iterated.
+///
+/// A range is a sequence rather than an interval, so `2.5 in 0..5` is false, since iterating over
+/// `0..5` never yields `2.5`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Range {
+    pub start: Option<f32>,
+    pub end: Option<f32>,
+    pub step: f32,
+    pub inclusive: bool,
+}
+
+impl Range {
+    fn before_end(&self, x: f32) -> bool {
+        match self.end {
+            None => true,
+            Some(end) if self.step > 0. => x < end || (self.inclusive && x == end),
+            Some(end) => x > end || (self.inclusive && x == end),
+        }
+    }
+
+    pub fn contains(&self, x: f32) -> bool {
+        let after_start = match self.start {
+            None => true,
+            Some(start) if self.step > 0. => x >= start,
+            Some(start) => x <= start,
+        };
+
+        let aligned = match self.start {
+            Some(start) => ((x - start) / self.step).fract() == 0.,
+            None => true,
+        };
+
+        after_start && aligned && self.before_end(x)
+    }
+
+    pub fn iter(&self) -> Result<RangeIter, EvalError> {
+        let Some(start) = self.start else {
+            return Err(EvalError::NotIterable("Range without a start".into()));
+        };
+
+        Ok(RangeIter {
+            range: *self,
+            start,
+            idx: 0,
+            prev: None,
+        })
+    }
+
+    /// Resolves `self` to the indices it selects from a sequence of length `len`, for slicing.
+    pub(crate) fn slice_indices(
+        &self,
+        len: usize,
+    ) -> Result<impl Iterator<Item = usize>, EvalError> {
+        if self.step < 1. || self.step.fract() != 0. {
+            return Err(EvalError::InvalidRangeStep);
+        }
+
+        let start = expect_index(self.start.unwrap_or(0.))?;
+        let end = match self.end {
+            Some(end) => expect_index(end)? + usize::from(self.inclusive),
+            None => len,
+        };
+
+        if end > len || start > end {
+            return Err(EvalError::IndexOutOfBounds {
+                index: end.max(start) as i64,
+                len,
+            });
+        }
+
+        Ok((start..end).step_by(self.step as usize))
+    }
+}
+
+/// Iterator over the elements of a [`Range`]. Elements are computed from the start and the
+/// number of steps taken, so that rounding errors don't accumulate.
+///
+/// Once the elements grow too large for consecutive ones to be told apart as `f32`s, e.g. after
+/// 2^24 steps of `0..`, the iterator ends instead of repeating the same element.
+#[derive(Debug, Clone)]
+pub struct RangeIter {
+    range: Range,
+    start: f32,
+    idx: u64,
+    prev: Option<f32>,
+}
+
+impl Iterator for RangeIter {
+    type Item = Val;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = (f64::from(self.start) + self.idx as f64 * f64::from(self.range.step)) as f32;
+
+        if !self.range.before_end(x) || self.prev == Some(x) {
+            return None;
+        }
+
+        self.idx += 1;
+        self.prev = Some(x);
+        Some(Val::Real(x))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RangeExpr {
+    pub start: Option<Expr>,
+    pub end: Option<Expr>,
+    pub inclusive: bool,
+    pub step: Option<Expr>,
+}
+
+impl RangeExpr {
+    /// Parses a range, or only its start if no `..` follows it. Ranges bind looser than
+    /// arithmetic, so `0..n + 1` ends at `n + 1`.
+    pub(crate) fn parse_or_operand(s: &str) -> crate::ParseOutput<Expr> {
+        let (_, trimmed) = extract_whitespace(s);
+        let (s, start) = if trimmed.starts_with(RANGE_TOKEN) {
+            (trimmed, None)
+        } else {
+            let (s, start) = Expr::parse_arith(s)?;
+            (s, Some(start))
+        };
+
+        match (Self::parse_rest(&s, start.clone()), start) {
+            (Ok((s, range)), _) => Ok((s, Expr::range(range))),
+            (Err(_), Some(start)) => Ok((s, start)),
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    fn parse_rest(s: &str, start: Option<Expr>) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = match s.strip_prefix(RANGE_TOKEN) {
+            // a third dot is the spread operator instead
+            Some(rest) if !rest.starts_with('.') => rest,
+            _ => {
+                return Err(ParseError::SequenceNotFound {
+                    expected: RANGE_TOKEN.into(),
+                    received: s,
+                });
+            }
+        };
+
+        let (inclusive, s) = match s.strip_prefix(INCLUSIVE_TOKEN) {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+
+        let (s, end) = match Expr::parse_arith(s) {
+            Ok((s, end)) => (s, Some(end)),
+            Err(e) if inclusive => return Err(e),
+            Err(_) => (s.into(), None),
+        };
+
+        let (_, after_ws) = extract_whitespace(&s);
+        let (s, step) = if starts_with_keyword(STEP_KW, &after_ws) {
+            let (s, step) = Expr::parse_arith(&after_ws[STEP_KW.len()..])?;
+            (s, Some(step))
+        } else {
+            (s, None)
+        };
+
+        Ok((
+            s,
+            Self {
+                start,
+                end,
+                inclusive,
+                step,
+            },
+        ))
+    }
+}
+
+impl Parse for RangeExpr {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, trimmed) = extract_whitespace(s);
+        if trimmed.starts_with(RANGE_TOKEN) {
+            return Self::parse_rest(&trimmed, None);
+        }
+
+        let (s, start) = Expr::parse_arith(s)?;
+        Self::parse_rest(&s, Some(start))
+    }
+}
+
+impl Eval for RangeExpr {
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, EvalError> {
+        let mut bound = |expr: &Option<Expr>| -> Result<Option<f32>, EvalError> {
+            match expr.as_ref().map(|e| e.eval(env)).transpose()? {
+                None => Ok(None),
+                Some(Val::Real(r)) => Ok(Some(r)),
+                Some(v) => Err(EvalError::InvalidType {
+                    expected: "a real number as range bound".into(),
+                    received: v.get_type().into(),
+                }),
+            }
+        };
+
+        let start = bound(&self.start)?;
+        let end = bound(&self.end)?;
+        let step = bound(&self.step)?.unwrap_or(1.);
+
+        if step == 0. || step.is_nan() {
+            return Err(EvalError::InvalidRangeStep);
+        }
+
+        Ok(Val::Range(Range {
+            start,
+            end,
+            step,
+            inclusive: self.inclusive,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EvalError, Parse,
+        test_utils::{eval_src, reals},
+        val::Val,
+    };
+
+    use super::{Range, RangeExpr};
+
+    #[test]
+    fn parse_range() {
+        let (s, range) = RangeExpr::parse("0..=n + 1 step 2").unwrap();
+
+        assert_eq!(s, "");
+        assert!(range.start.is_some() && range.end.is_some() && range.step.is_some());
+        assert!(range.inclusive)
+    }
+
+    #[test]
+    fn parse_open_range() {
+        let (s, range) = RangeExpr::parse("..5").unwrap();
+
+        assert_eq!(s, "");
+        assert!(range.start.is_none() && range.end.is_some())
+    }
+
+    #[test]
+    fn eval_range_is_lazy() {
+        assert_eq!(
+            eval_src("0..1000000"),
+            Ok(Val::Range(Range {
+                start: Some(0.),
+                end: Some(1000000.),
+                step: 1.,
+                inclusive: false
+            }))
+        )
+    }
+
+    #[test]
+    fn iterate_ranges() {
+        let collect = |src: &str| {
+            let Ok(Val::Range(range)) = eval_src(src) else {
+                panic!("expected a range")
+            };
+            Val::List(range.iter().unwrap().collect())
+        };
+
+        assert_eq!(collect("0..3"), reals(&[0., 1., 2.]));
+        assert_eq!(collect("1..=3"), reals(&[1., 2., 3.]));
+        assert_eq!(collect("0..10 step 4"), reals(&[0., 4., 8.]));
+        assert_eq!(collect("3..0 step 0 - 1"), reals(&[3., 2., 1.]))
+    }
+
+    #[test]
+    fn iteration_ends_at_the_limit_of_precision() {
+        let range = Range {
+            start: Some(16777214.),
+            end: None,
+            step: 1.,
+            inclusive: false,
+        };
+
+        assert_eq!(
+            Val::List(range.iter().unwrap().collect()),
+            reals(&[16777214., 16777215., 16777216.])
+        )
+    }
+
+    #[test]
+    fn for_over_range() {
+        assert_eq!(
+            eval_src("bind sum = 0; for x in 1..=4 { sum += x }; sum"),
+            Ok(Val::Real(10.))
+        )
+    }
+
+    #[test]
+    fn zero_step() {
+        assert_eq!(eval_src("0..5 step 0"), Err(EvalError::InvalidRangeStep))
+    }
+
+    #[test]
+    fn iterate_open_start() {
+        assert_eq!(
+            eval_src("for x in ..5 { x }"),
+            Err(EvalError::NotIterable("Range without a start".into()))
+        )
+    }
+}
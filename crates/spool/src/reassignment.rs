@@ -1,39 +1,118 @@
 use crate::{
-    Eval, Parse,
+    Eval, EvalError, Parse,
     binding::{ASSIGN_TOKEN, Identifier},
     expr::Expr,
+    lit::Op,
+    member::{parse_dot, type_name},
     utils::{extract_whitespace, tag},
+    val::Val,
 };
 
+/// `x = expr`, or a compound assignment such as `x += expr` when `op` is set. The target may be a
+/// field of a struct instance, such as `self.n += 1`, in which case `fields` holds the path.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Reassignment {
     pub lhs: Identifier,
+    pub fields: Vec<Identifier>,
+    pub op: Option<Op>,
     pub rhs: Expr,
 }
 
+impl Reassignment {
+    /// Applies `op` to the field at the end of `path` within `target` and the new value.
+    fn assign_field(&self, target: Val, path: &[Identifier], rhs: Val) -> Result<(), EvalError> {
+        let unknown = |member: &Identifier| EvalError::UnknownMember {
+            ty: type_name(&target),
+            member: member.clone(),
+        };
+
+        let Val::Struct(ref inst) = target else {
+            return Err(unknown(&path[0]));
+        };
+
+        let current = inst
+            .lock()
+            .unwrap()
+            .fields
+            .get(&path[0])
+            .cloned()
+            .ok_or_else(|| unknown(&path[0]))?;
+
+        if path.len() > 1 {
+            return self.assign_field(current, &path[1..], rhs);
+        }
+
+        let new_val = match self.op {
+            Some(op) => op.apply(current, rhs)?,
+            None => rhs,
+        };
+
+        inst.lock().unwrap().fields.insert(path[0].clone(), new_val);
+        Ok(())
+    }
+}
+
 impl Parse for Reassignment {
     fn parse(s: &str) -> crate::ParseOutput<Self> {
         let (_, s) = extract_whitespace(s);
 
-        let (s, lhs) = Identifier::parse(&s)?;
+        let (mut s, lhs) = Identifier::parse(&s)?;
+
+        let mut fields = vec![];
+        while let Ok(rest) = parse_dot(&s) {
+            let (rest, field) = Identifier::parse(rest)?;
+            fields.push(field);
+            s = rest;
+        }
+
+        let (s, op) = match Op::parse(&s) {
+            Ok((s, op)) => (s, Some(op)),
+            Err(_) => (s, None),
+        };
 
         let (_, s) = extract_whitespace(&s);
         let s = tag(ASSIGN_TOKEN, &s)?;
 
         let (s, rhs) = Expr::parse(&s)?;
 
-        Ok((s, Self { lhs, rhs }))
+        Ok((
+            s,
+            Self {
+                lhs,
+                fields,
+                op,
+                rhs,
+            },
+        ))
     }
 }
 
 impl Eval for Reassignment {
-    fn eval(&self, env: &mut crate::Env) -> Result<crate::val::Val, crate::EvalError> {
-        env.get_stored_binding(&self.lhs)?;
+    fn eval(&self, env: &mut crate::Env) -> Result<Val, EvalError> {
+        let (current, immutable) = env.get_stored_binding(&self.lhs)?;
 
         let rhs_val = self.rhs.eval(env)?;
-        env.reassign_binding(self.lhs.clone(), rhs_val)?;
 
-        Ok(crate::val::Val::Unit)
+        // instances are shared, so a field is updated in place rather than through the binding.
+        // `final` only forbids assigning through that binding: other bindings of the same
+        // instance, such as `self` in a method, can still change its fields
+        if !self.fields.is_empty() {
+            if immutable {
+                return Err(EvalError::ImmutableReassignment(self.lhs.clone()));
+            }
+
+            self.assign_field(current, &self.fields, rhs_val)?;
+            return Ok(Val::Unit);
+        }
+
+        let new_val = match self.op {
+            Some(op) => op.apply(current, rhs_val)?,
+            None => rhs_val,
+        };
+
+        env.reassign_binding(self.lhs.clone(), new_val)?;
+
+        Ok(Val::Unit)
     }
 }
 
@@ -55,6 +134,8 @@ mod tests {
                 "".into(),
                 Reassignment {
                     lhs: "x".into(),
+                    fields: vec![],
+                    op: None,
                     rhs: Expr::math_expr(
                         MathExpr {
                             lhs: Expr::simple(crate::lit::Literal::Real(crate::lit::LitReal(5.))),
@@ -75,13 +156,62 @@ mod tests {
 
         let _ = Reassignment {
             lhs: "x".into(),
+            fields: vec![],
+            op: None,
             rhs: Expr::simple(crate::lit::Literal::Bool(crate::lit::LitBool(false))),
         }
         .eval(&mut env);
 
         assert_eq!(
             env.get_stored_binding(&"x".into()),
-            Ok((crate::val::Val::Bool(true), false))
+            Ok((crate::val::Val::Bool(false), false))
+        )
+    }
+
+    #[test]
+    fn parse_compound_reassignment() {
+        assert_eq!(
+            Reassignment::parse("x %= 2"),
+            Ok((
+                "".into(),
+                Reassignment {
+                    lhs: "x".into(),
+                    fields: vec![],
+                    op: Some(Op::Rem),
+                    rhs: Expr::simple(crate::lit::Literal::Real(crate::lit::LitReal(2.)))
+                }
+            ))
+        )
+    }
+
+    #[test]
+    fn eval_compound_reassignment() {
+        let mut env = Env::new();
+        env.store_binding("x".into(), crate::val::Val::Real(5.), false);
+
+        let (_, stmt) = crate::stmt::Stmt::parse("x -= 2").unwrap();
+        assert_eq!(stmt.eval(&mut env), Ok(crate::val::Val::Unit));
+
+        assert_eq!(
+            env.get_stored_binding(&"x".into()),
+            Ok((crate::val::Val::Real(3.), false))
+        )
+    }
+
+    #[test]
+    fn do_not_compound_reassign_immutable() {
+        let mut env = Env::new();
+        env.store_binding("x".into(), crate::val::Val::Real(5.), true);
+
+        assert_eq!(
+            Reassignment {
+                lhs: "x".into(),
+                fields: vec![],
+                op: Some(Op::Mul),
+                rhs: Expr::simple(crate::lit::Literal::Real(crate::lit::LitReal(2.)))
+            }
+            .eval(&mut env),
+            Err(crate::EvalError::ImmutableReassignment("x".into()))
         )
     }
 }
@@ -0,0 +1,107 @@
+use crate::{Env, Eval, EvalError, ParseError, error::ErrorVal, expr::Expr, val::Val};
+
+const PROPAGATE_TOKEN: &str = "?";
+
+impl Val {
+    pub fn ok(val: Val) -> Self {
+        Self::Result(Box::new(Ok(val)))
+    }
+
+    pub fn err(val: Val) -> Self {
+        Self::Result(Box::new(Err(val)))
+    }
+
+    /// An `Err` holding an [`ErrorVal`], as returned by library functions that fail.
+    pub(crate) fn err_with(kind: &str, message: impl Into<String>) -> Self {
+        Self::err(Self::Error(ErrorVal {
+            kind: kind.into(),
+            message: message.into(),
+        }))
+    }
+}
+
+/// The postfix `?` operator, as in `parseReal(s)?`. Unwraps an `Ok`, or returns the `Err` from
+/// the enclosing function.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PropagateExpr {
+    pub expr: Expr,
+}
+
+impl PropagateExpr {
+    /// Parses the `?` that follows the operand. Like indexing, it must directly follow the
+    /// operand. `?.` and `??` are left alone for optional chaining and `nil` coalescing.
+    pub(crate) fn parse_token(s: &str) -> Result<&str, ParseError> {
+        match s.strip_prefix(PROPAGATE_TOKEN) {
+            Some(rest) if !rest.starts_with(['.', '?']) => Ok(rest),
+            _ => Err(ParseError::SequenceNotFound {
+                expected: PROPAGATE_TOKEN.into(),
+                received: s.into(),
+            }),
+        }
+    }
+}
+
+impl Eval for PropagateExpr {
+    fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        match self.expr.eval(env)? {
+            Val::Result(res) => match *res {
+                Ok(v) => Ok(v),
+                Err(e) => Err(EvalError::Return(Val::err(e))),
+            },
+            v => Err(EvalError::InvalidType {
+                expected: "a result".into(),
+                received: v.get_type().into(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvalError, error::ErrorVal, test_utils::eval_src, val::Val};
+
+    #[test]
+    fn propagate_ok() {
+        assert_eq!(
+            eval_src("func double(r) => Ok(r? * 2); double(Ok(2))"),
+            Ok(Val::ok(Val::Real(4.)))
+        )
+    }
+
+    #[test]
+    fn propagate_err_returns_early() {
+        assert_eq!(
+            eval_src(
+                "func f(r) => { bind n = r?; throw \"unreachable\" }
+                f(Err(\"bad\"))"
+            ),
+            Ok(Val::err(Val::Str("bad".into())))
+        )
+    }
+
+    #[test]
+    fn propagate_library_error() {
+        assert_eq!(
+            eval_src("func sum(a, b) => Ok(parseReal(a)? + parseReal(b)?); sum(\"1\", \"x\")"),
+            Ok(Val::err(Val::Error(ErrorVal {
+                kind: "ParseError".into(),
+                message: "\"x\" is not a real number".into()
+            })))
+        );
+        assert_eq!(
+            eval_src("func sum(a, b) => Ok(parseReal(a)? + parseReal(b)?); sum(\"1\", \"2.5\")"),
+            Ok(Val::ok(Val::Real(3.5)))
+        )
+    }
+
+    #[test]
+    fn propagate_non_result() {
+        assert_eq!(
+            eval_src("func f() => 1?; f()"),
+            Err(EvalError::InvalidType {
+                expected: "a result".into(),
+                received: "Real number".into()
+            })
+        )
+    }
+}
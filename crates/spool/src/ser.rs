@@ -0,0 +1,209 @@
+use std::{cell::RefCell, collections::BTreeMap, fmt, sync::Arc};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeStruct},
+};
+
+use crate::val::Val;
+
+thread_local! {
+    /// The instances being serialized further up the stack of the current thread.
+    static SERIALIZING: RefCell<Vec<usize>> = const { RefCell::new(vec![]) };
+}
+
+/// Serializes lists as sequences, maps, struct instances and errors as maps, `nil` as none and
+/// `()` as unit. Results are serialized as the enum variants `Ok` and `Err`. Values that are
+/// behavior rather than data, like functions, fail to serialize, and so do instances that
+/// contain themselves.
+impl Serialize for Val {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Str(s) => serializer.serialize_str(s),
+            Self::Real(r) => serializer.serialize_f32(*r),
+            Self::Bool(b) => serializer.serialize_bool(*b),
+            Self::List(items) => serializer.collect_seq(items),
+            Self::Map(map) => serializer.collect_map(map),
+            Self::Range(range) => {
+                let mut s = serializer.serialize_struct("Range", 4)?;
+                s.serialize_field("start", &range.start)?;
+                s.serialize_field("end", &range.end)?;
+                s.serialize_field("step", &range.step)?;
+                s.serialize_field("inclusive", &range.inclusive)?;
+                s.end()
+            }
+            Self::Struct(inst) => {
+                // the fields are copied out, since serializing them may lock the same instance
+                let ptr = Arc::as_ptr(inst) as usize;
+                if SERIALIZING.with(|s| s.borrow().contains(&ptr)) {
+                    return Err(ser::Error::custom("cyclic struct instance"));
+                }
+                let fields = inst.lock().unwrap().fields.clone();
+
+                SERIALIZING.with(|s| s.borrow_mut().push(ptr));
+                let out = serializer.collect_map(fields.iter().map(|(k, v)| (&k.0, v)));
+                SERIALIZING.with(|s| s.borrow_mut().pop());
+
+                out
+            }
+            Self::Error(e) => {
+                let mut s = serializer.serialize_struct("Error", 2)?;
+                s.serialize_field("kind", &e.kind)?;
+                s.serialize_field("message", &e.message)?;
+                s.end()
+            }
+            Self::Result(res) => match &**res {
+                Ok(v) => serializer.serialize_newtype_variant("Result", 0, "Ok", v),
+                Err(v) => serializer.serialize_newtype_variant("Result", 1, "Err", v),
+            },
+            Self::Nil => serializer.serialize_none(),
+            Self::Unit => serializer.serialize_unit(),
+            Self::Func(_) | Self::Iter(_) | Self::Module(_) => Err(ser::Error::custom(format!(
+                "a value of type {} can't be serialized",
+                self.get_type()
+            ))),
+        }
+    }
+}
+
+/// Deserializes any self-describing data: numbers become real numbers, sequences lists and maps
+/// maps, whose keys have to be strings. Both none and unit become `nil`.
+impl<'de> Deserialize<'de> for Val {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValVisitor)
+    }
+}
+
+struct ValVisitor;
+
+impl<'de> Visitor<'de> for ValVisitor {
+    type Value = Val;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Spool value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Val, E> {
+        Ok(Val::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Val, E> {
+        Ok(Val::Real(v as f32))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Val, E> {
+        Ok(Val::Real(v as f32))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Val, E> {
+        Ok(Val::Real(v as f32))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Val, E> {
+        Ok(Val::Str(v.into()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Val, E> {
+        Ok(Val::Str(v))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Val, E> {
+        Ok(Val::Nil)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Val, D::Error> {
+        Val::deserialize(deserializer)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Val, E> {
+        Ok(Val::Nil)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Val, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Val::List(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Val, A::Error> {
+        let mut out = BTreeMap::new();
+        while let Some((k, v)) = map.next_entry::<String, Val>()? {
+            out.insert(k, v);
+        }
+        Ok(Val::Map(out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{Interpreter, Val};
+
+    fn to_json(src: &str) -> Result<serde_json::Value, String> {
+        let val = Interpreter::new().eval_str(src).unwrap();
+        serde_json::to_value(val).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn serialize() {
+        assert_eq!(
+            to_json("struct P { x, y }; [\"p\": P(1, 2), \"l\": [true, nil, \"s\"], \"r\": Ok(1)]"),
+            Ok(json!({
+                "p": { "x": 1.0, "y": 2.0 },
+                "l": [true, null, "s"],
+                "r": { "Ok": 1.0 }
+            }))
+        );
+        assert_eq!(
+            to_json("try { throw 1 } catch e { parseReal(\"x\") }"),
+            Ok(json!({ "Err": { "kind": "ParseError", "message": "\"x\" is not a real number" } }))
+        )
+    }
+
+    #[test]
+    fn serialize_functions_fails() {
+        assert_eq!(
+            to_json("func f() => 1; [\"f\": f]"),
+            Err("a value of type Function can't be serialized".into())
+        );
+        assert_eq!(
+            to_json("[1, 2].map(func(x) => x)"),
+            Err("a value of type Iterator can't be serialized".into())
+        )
+    }
+
+    #[test]
+    fn serialize_cyclic_instance_fails() {
+        let src = "struct N { next = nil }; bind a = N(); a.next = a";
+        assert_eq!(
+            to_json(&format!("{src}; a")),
+            Err("cyclic struct instance".into())
+        );
+
+        let val = Interpreter::new().eval_str(&format!("{src}; a")).unwrap();
+        assert!(serde_json::to_string(&val).is_err());
+
+        // an instance that's merely shared isn't a cycle
+        assert_eq!(
+            to_json("struct P { x }; bind p = P(1); [p, p]"),
+            Ok(json!([{ "x": 1.0 }, { "x": 1.0 }]))
+        )
+    }
+
+    #[test]
+    fn deserialize() {
+        let val: Val =
+            serde_json::from_value(json!({ "a": [1, "b", null], "c": { "d": false } })).unwrap();
+
+        let mut interp = Interpreter::new();
+        interp.set_global("fixture", val);
+        assert_eq!(
+            interp.eval_str("[fixture.a[0], fixture.a[2], fixture.c.d]"),
+            Ok(Val::List(vec![Val::Real(1.), Val::Nil, Val::Bool(false)]))
+        )
+    }
+}
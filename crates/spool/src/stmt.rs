@@ -1,4 +1,18 @@
-use crate::{Eval, Parse, binding::Binding, expr::Expr, func::FuncDef, reassignment::Reassignment};
+use crate::{
+    Eval, Parse, ParseError,
+    binding::{BIND_TOKEN, Binding},
+    error::{THROW_KW, Throw},
+    expr::Expr,
+    func::{FUNC_KW, FuncDef},
+    generator::{YIELD_KW, Yield},
+    loops::{BREAK_KW, CONTINUE_KW, FOR_KW, ForLoop},
+    module::{EXPORT_KW, Export, IMPORT_KW, Import},
+    reassignment::Reassignment,
+    structs::{STRUCT_KW, StructDef},
+    utils::{extract_inline_whitespace, extract_whitespace, starts_with_keyword},
+};
+
+const STMT_TERMINATOR: char = ';';
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Stmt {
@@ -6,17 +20,107 @@ pub enum Stmt {
     Expr(Expr),
     Func(FuncDef),
     Reassignment(Reassignment),
+    For(ForLoop),
+    Struct(StructDef),
+    Yield(Yield),
+    Throw(Throw),
+    Import(Import),
+    Export(Export),
+    Break,
+    Continue,
 }
 
 impl Parse for Stmt {
     fn parse(s: &str) -> crate::ParseOutput<Self> {
-        Binding::parse(s)
-            .map(|(s, p)| (s, Self::Binding(p)))
+        let (_, trimmed) = extract_whitespace(s);
+
+        // statements introduced by a keyword report their own errors, instead of the error of
+        // whichever alternative happened to be tried last
+        if starts_with_keyword(BIND_TOKEN, &trimmed) {
+            return Binding::parse(s).map(|(s, p)| (s, Self::Binding(p)));
+        }
+
+        if starts_with_keyword(FUNC_KW, &trimmed) {
+            return FuncDef::parse(s).map(|(s, p)| (s, Self::Func(p)));
+        }
+
+        if starts_with_keyword(FOR_KW, &trimmed) {
+            return ForLoop::parse(s).map(|(s, p)| (s, Self::For(p)));
+        }
+
+        if starts_with_keyword(STRUCT_KW, &trimmed) {
+            return StructDef::parse(s).map(|(s, p)| (s, Self::Struct(p)));
+        }
+
+        if starts_with_keyword(YIELD_KW, &trimmed) {
+            return Yield::parse(s).map(|(s, p)| (s, Self::Yield(p)));
+        }
+
+        if starts_with_keyword(IMPORT_KW, &trimmed) {
+            return Import::parse(s).map(|(s, p)| (s, Self::Import(p)));
+        }
+
+        if starts_with_keyword(EXPORT_KW, &trimmed) {
+            return Export::parse(s).map(|(s, p)| (s, Self::Export(p)));
+        }
+
+        if starts_with_keyword(THROW_KW, &trimmed) {
+            return Throw::parse(s).map(|(s, p)| (s, Self::Throw(p)));
+        }
+
+        if starts_with_keyword(BREAK_KW, &trimmed) {
+            return Ok((trimmed[BREAK_KW.len()..].into(), Self::Break));
+        }
+
+        if starts_with_keyword(CONTINUE_KW, &trimmed) {
+            return Ok((trimmed[CONTINUE_KW.len()..].into(), Self::Continue));
+        }
+
+        Reassignment::parse(s)
+            .map(|(s, p)| (s, Self::Reassignment(p)))
             .or_else(|_| Expr::parse(s).map(|(s, p)| (s, Self::Expr(p))))
-            .or_else(|_| FuncDef::parse(s).map(|(s, p)| (s, Self::Func(p))))
     }
 }
 
+/// Parses a sequence of statements, separated by `;` or line breaks, until `is_end` matches the
+/// remaining input. Empty statements are allowed, so `;;` or blank lines are skipped.
+pub(crate) fn parse_stmts(s: &str, is_end: impl Fn(&str) -> bool) -> crate::ParseOutput<Vec<Stmt>> {
+    let mut stmts = vec![];
+    let mut s = skip_separators(s);
+
+    while !is_end(&s) {
+        let (rest, stmt) = Stmt::parse(&s)?;
+        stmts.push(stmt);
+
+        let (_, rest) = extract_inline_whitespace(&rest);
+        if is_end(&rest) {
+            s = rest;
+            break;
+        }
+
+        if !rest.starts_with([STMT_TERMINATOR, '\n', '\r']) {
+            return Err(ParseError::SequenceNotFound {
+                expected: format!("`{STMT_TERMINATOR}` or a line break"),
+                received: rest,
+            });
+        }
+
+        s = skip_separators(&rest);
+    }
+
+    Ok((s, stmts))
+}
+
+fn skip_separators(s: &str) -> String {
+    let mut s = extract_whitespace(s).1;
+
+    while let Some(rest) = s.strip_prefix(STMT_TERMINATOR) {
+        s = extract_whitespace(rest).1;
+    }
+
+    s
+}
+
 impl Eval for Stmt {
     fn eval(&self, env: &mut crate::env::Env) -> Result<crate::val::Val, crate::EvalError> {
         match self {
@@ -24,13 +128,23 @@ impl Eval for Stmt {
             Self::Expr(e) => e.eval(env),
             Self::Func(f) => f.eval(env),
             Self::Reassignment(r) => r.eval(env),
+            Self::For(f) => f.eval(env),
+            Self::Struct(s) => s.eval(env),
+            Self::Yield(y) => y.eval(env),
+            Self::Throw(t) => t.eval(env),
+            Self::Import(i) => i.eval(env),
+            Self::Export(e) => e.eval(env),
+            Self::Break => Err(crate::EvalError::Break),
+            Self::Continue => Err(crate::EvalError::Continue),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Eval, Parse, binding::Binding, env::Env, func::FuncDef, stmt::Stmt, val::Val};
+    use crate::{
+        Eval, Parse, ParseError, binding::Binding, env::Env, func::FuncDef, stmt::Stmt, val::Val,
+    };
 
     #[test]
     fn eval_binding_stmt() {
@@ -61,4 +175,62 @@ mod tests {
             ))
         )
     }
+
+    #[test]
+    fn parse_reassignment() {
+        assert_eq!(
+            Stmt::parse("x = 5"),
+            Ok((
+                "".into(),
+                Stmt::Reassignment(crate::reassignment::Reassignment {
+                    lhs: "x".into(),
+                    fields: vec![],
+                    op: None,
+                    rhs: crate::expr::Expr::simple(crate::lit::Literal::Real(crate::lit::LitReal(
+                        5.
+                    )))
+                })
+            ))
+        )
+    }
+
+    #[test]
+    fn parse_stmts_with_separators() {
+        let (s, stmts) = super::parse_stmts("bind x = 5; x\n\n  x;", str::is_empty).unwrap();
+
+        assert_eq!(s, "");
+        assert_eq!(stmts.len(), 3);
+    }
+
+    #[test]
+    fn parse_stmts_requires_separator() {
+        assert_eq!(
+            super::parse_stmts("bind x = 5 x", str::is_empty),
+            Err(ParseError::SequenceNotFound {
+                expected: "`;` or a line break".into(),
+                received: "x".into()
+            })
+        )
+    }
+
+    #[test]
+    fn line_break_ends_binary_operation() {
+        // `-3` is a statement of its own, rather than being subtracted from `5`
+        assert!(crate::Parsed::parse("bind x = 5\n-3\nx").is_err());
+
+        // an operator at the end of a line still continues the expression
+        let (_, parsed) = crate::Parsed::parse("bind x = 5 -\n3\nx").unwrap();
+        assert_eq!(parsed.eval(&mut Env::new()), Ok(Val::Real(2.)))
+    }
+
+    #[test]
+    fn parse_broken_keyword_stmt() {
+        assert_eq!(
+            Stmt::parse("bind x 5"),
+            Err(ParseError::SequenceNotFound {
+                expected: "=".into(),
+                received: "5".into()
+            })
+        )
+    }
 }
@@ -0,0 +1,223 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    Env, Eval, EvalError, Parse,
+    binding::Identifier,
+    env::{Captured, NamelessFunction},
+    fn_call::{CallArgs, bind_params},
+    func::{FUNC_KW, FuncDef, Param},
+    utils::{extract_whitespace, starts_with_keyword, tag},
+    val::Val,
+};
+
+pub(crate) const STRUCT_KW: &str = "struct";
+
+/// A struct declaration, listing its fields (optionally with defaults) and methods:
+///
+/// ```text
+/// struct Counter {
+///     n = 0
+///     func next(self) => { self.n += 1; self.n }
+/// }
+/// ```
+///
+/// Structs are instantiated by calling them like a function, e.g. `Counter(n: 5)`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StructDef {
+    pub(crate) id: Identifier,
+    pub(crate) fields: Vec<Param>,
+    pub(crate) methods: HashMap<Identifier, NamelessFunction>,
+}
+
+impl StructDef {
+    pub(crate) fn construct(def: &Arc<Self>, args: CallArgs) -> Result<Val, EvalError> {
+        let env = bind_params(&def.fields, args, Env::new())?;
+
+        let mut fields = BTreeMap::new();
+        for field in &def.fields {
+            let (val, _) = env.get_stored_binding(&field.id)?;
+            fields.insert(field.id.clone(), val);
+        }
+
+        Ok(Val::Struct(Arc::new(Mutex::new(Instance {
+            def: def.clone(),
+            fields,
+        }))))
+    }
+
+    /// Skips whitespace and the separators allowed between members.
+    fn skip_separators(s: &str) -> String {
+        let mut s = extract_whitespace(s).1;
+
+        while let Some(rest) = s.strip_prefix([',', ';']) {
+            s = extract_whitespace(rest).1;
+        }
+
+        s
+    }
+}
+
+impl Parse for StructDef {
+    fn parse(s: &str) -> crate::ParseOutput<Self> {
+        let (_, s) = extract_whitespace(s);
+        let s = tag(STRUCT_KW, &s)?;
+
+        let (s, id) = Identifier::parse(&s)?;
+
+        let (_, s) = extract_whitespace(&s);
+        let mut s = Self::skip_separators(&tag("{", &s)?);
+
+        let mut fields = vec![];
+        let mut methods = HashMap::new();
+
+        while !s.starts_with('}') {
+            if starts_with_keyword(FUNC_KW, &s) {
+                let (new_s, method) = FuncDef::parse(&s)?;
+                methods.insert(
+                    method.id,
+                    NamelessFunction {
+                        params: method.params,
+                        body: method.body,
+                        globals: None,
+                        captured: Captured::default(),
+                    },
+                );
+                s = new_s;
+            } else {
+                let (new_s, field) = Param::parse(&s)?;
+                fields.push(field);
+                s = new_s;
+            }
+
+            s = Self::skip_separators(&s);
+        }
+
+        let s = tag("}", &s)?;
+
+        Ok((
+            s,
+            Self {
+                id,
+                fields,
+                methods,
+            },
+        ))
+    }
+}
+
+impl Eval for StructDef {
+    fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        env.store_struct(self.id.clone(), self.clone());
+        Ok(Val::Unit)
+    }
+}
+
+/// An instance of a struct. Instances are shared by reference, so a method that modifies `self`
+/// modifies the instance it was called on.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Instance {
+    pub(crate) def: Arc<StructDef>,
+    pub(crate) fields: BTreeMap<Identifier, Val>,
+}
+
+impl Instance {
+    pub(crate) fn method(&self, id: &Identifier) -> Option<NamelessFunction> {
+        self.def.methods.get(id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EvalError, Parse, test_utils::eval_src, val::Val};
+
+    use super::StructDef;
+
+    #[test]
+    fn parse_struct() {
+        let (s, def) = StructDef::parse(
+            "struct Point {\n    x, y = 0\n    func sum(self) => self.x + self.y\n}",
+        )
+        .unwrap();
+
+        assert_eq!(s, "");
+        assert_eq!(def.fields.len(), 2);
+        assert!(def.methods.contains_key(&"sum".into()))
+    }
+
+    #[test]
+    fn construct_and_access() {
+        assert_eq!(
+            eval_src("struct Point { x, y = 0 }; bind p = Point(1, y: 2); [p.x, p.y]"),
+            Ok(Val::List(vec![Val::Real(1.), Val::Real(2.)]))
+        );
+        assert_eq!(
+            eval_src("struct Point { x, y = 0 }; Point(1).y"),
+            Ok(Val::Real(0.))
+        )
+    }
+
+    #[test]
+    fn call_method() {
+        assert_eq!(
+            eval_src(
+                "struct Point { x, y; func sum(self, z) => self.x + self.y + z }; Point(1, 2).sum(3)"
+            ),
+            Ok(Val::Real(6.))
+        )
+    }
+
+    #[test]
+    fn method_mutates_self() {
+        assert_eq!(
+            eval_src(
+                "struct Counter { n = 0; func bump(self) => { self.n += 1 } }; bind c = Counter(); c.bump(); c.bump(); c.n"
+            ),
+            Ok(Val::Real(2.))
+        )
+    }
+
+    #[test]
+    fn assign_field_of_final_binding() {
+        assert_eq!(
+            eval_src("struct S { a }; bind final s = S(1); s.a = 2"),
+            Err(EvalError::ImmutableReassignment("s".into()))
+        );
+        // `final` is shallow, so the instance itself can still change through other bindings
+        assert_eq!(
+            eval_src(
+                "struct Counter { n = 0; func bump(self) => { self.n += 1 } }; bind final c = Counter(); c.bump(); c.n"
+            ),
+            Ok(Val::Real(1.))
+        )
+    }
+
+    #[test]
+    fn compare_cyclic_instances() {
+        let Ok(Val::List(nodes)) = eval_src(
+            "struct Node { next = nil }
+            bind a = Node(); bind b = Node(); bind c = Node()
+            a.next = b; b.next = a; c.next = c
+            [a, b, c, Node(1)]",
+        ) else {
+            panic!("expected a list")
+        };
+
+        assert_eq!(nodes[0], nodes[1]);
+        assert_eq!(nodes[0], nodes[2]);
+        assert_ne!(nodes[0], nodes[3])
+    }
+
+    #[test]
+    fn unknown_field() {
+        assert_eq!(
+            eval_src("struct Point { x }; Point(1).z"),
+            Err(EvalError::UnknownMember {
+                ty: "Point".into(),
+                member: "z".into()
+            })
+        )
+    }
+}
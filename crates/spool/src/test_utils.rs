@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use crate::{Env, EvalError, Parsed, val::Val};
+
+/// Parses `src`, which has to be valid, and evaluates it in a fresh environment.
+pub(crate) fn eval_src(src: &str) -> Result<Val, EvalError> {
+    eval_in(src, &mut Env::new())
+}
+
+/// Parses `src`, which has to be valid, and evaluates it in `env`.
+pub(crate) fn eval_in(src: &str, env: &mut Env) -> Result<Val, EvalError> {
+    let (_, parsed) = Parsed::parse(src).unwrap();
+    parsed.eval(env)
+}
+
+pub(crate) fn reals(xs: &[f32]) -> Val {
+    Val::List(xs.iter().map(|x| Val::Real(*x)).collect())
+}
+
+/// A fresh directory of files for a test, which is removed once the fixture is dropped.
+pub(crate) struct Fixture(tempfile::TempDir);
+
+impl Fixture {
+    /// Writes `files`, given as the path of each file relative to the directory and its contents.
+    pub(crate) fn new(files: &[(&str, &str)]) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+
+        for (name, src) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, src).unwrap();
+        }
+
+        Self(dir)
+    }
+
+    /// Returns the path of `name` in the directory, canonicalized like the paths of modules.
+    pub(crate) fn path(&self, name: &str) -> PathBuf {
+        self.0.path().canonicalize().unwrap().join(name)
+    }
+}
@@ -4,7 +4,7 @@ fn take_while(s: &str, f: impl Fn(char) -> bool) -> (String, String) {
     let end = s
         .char_indices()
         .find_map(|(idx, c)| if f(c) { None } else { Some(idx) })
-        .unwrap_or_else(|| s.len());
+        .unwrap_or(s.len());
 
     (s[..end].into(), s[end..].into())
 }
@@ -13,6 +13,18 @@ pub fn extract_whitespace(s: &str) -> (String, String) {
     take_while(s, |c| c.is_ascii_whitespace())
 }
 
+/// Like [`extract_whitespace`], but stops at line breaks, which separate statements.
+pub fn extract_inline_whitespace(s: &str) -> (String, String) {
+    take_while(s, |c| c == ' ' || c == '\t')
+}
+
+/// Whether `s` starts with the keyword `kw` as a whole word, so that e.g. `bindings` doesn't count
+/// as the `bind` keyword.
+pub fn starts_with_keyword(kw: &str, s: &str) -> bool {
+    s.strip_prefix(kw)
+        .is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphanumeric()))
+}
+
 pub fn extract_string(s: &str) -> Result<(String, String), ParseError> {
     let s = tag("\"", s)?;
 
@@ -23,15 +35,24 @@ pub fn extract_string(s: &str) -> Result<(String, String), ParseError> {
 }
 
 pub fn extract_float(s: &str) -> (String, String) {
-    take_while(s, |c| c.is_ascii_digit() || c == '.')
+    let (int, rest) = take_while(s, |c| c.is_ascii_digit());
+
+    // only treat the dot as a decimal point if a digit follows, so that `0..5` is a range
+    match rest.strip_prefix('.') {
+        Some(frac) if frac.starts_with(|c: char| c.is_ascii_digit()) => {
+            let (frac, rest) = take_while(frac, |c| c.is_ascii_digit());
+            (format!("{int}.{frac}"), rest)
+        }
+        _ => (int, rest),
+    }
 }
 
 pub fn extract_op(s: &str) -> Result<(String, String), ParseError> {
     match s.chars().next() {
-        Some('+' | '-' | '*' | '/') => (),
+        Some('+' | '-' | '*' | '/' | '%') => (),
         e => {
             return Err(ParseError::SequenceNotFound {
-                expected: "[+, -, *, /]".into(),
+                expected: "[+, -, *, /, %]".into(),
                 received: format!("{e:?}"),
             });
         }
@@ -53,20 +74,19 @@ pub fn extract_ident(s: &str) -> Result<(String, String), ParseError> {
     {
         return Err(ParseError::InvalidSequence {
             expected: "an identifier not beginning with a digit".into(),
-            received: id.into(),
+            received: id,
         });
     }
 
-    Ok((id.into(), rest.into()))
+    Ok((id, rest))
 }
 
 pub fn tag(seq: &str, s: &str) -> Result<String, ParseError> {
-    if s.starts_with(seq) {
-        Ok(s[seq.len()..].into())
-    } else {
-        Err(ParseError::SequenceNotFound {
+    match s.strip_prefix(seq) {
+        Some(rest) => Ok(rest.into()),
+        None => Err(ParseError::SequenceNotFound {
             expected: seq.into(),
             received: s.into(),
-        })
+        }),
     }
 }
@@ -1,11 +1,78 @@
-#[derive(Debug, PartialEq, Clone)]
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    EvalError, env::NamelessFunction, error::ErrorVal, iter::IterRef, module::Module, range::Range,
+    structs::Instance,
+};
+
+#[derive(Debug, Clone)]
 pub enum Val {
     Str(String),
     Real(f32),
     Bool(bool),
+    List(Vec<Val>),
+    Map(BTreeMap<String, Val>),
+    Range(Range),
+    Func(Arc<NamelessFunction>),
+    Struct(Arc<Mutex<Instance>>),
+    Iter(IterRef),
+    Error(ErrorVal),
+    Result(Box<Result<Val, Val>>),
+    Module(Arc<Module>),
+    Nil,
     Unit,
 }
 
+/// Struct instances are equal if they're the same instance, or their fields are.
+impl PartialEq for Val {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Str(a), Self::Str(b)) => a == b,
+            (Self::Real(a), Self::Real(b)) => a == b,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::List(a), Self::List(b)) => a == b,
+            (Self::Map(a), Self::Map(b)) => a == b,
+            (Self::Range(a), Self::Range(b)) => a == b,
+            (Self::Func(a), Self::Func(b)) => a == b,
+            (Self::Struct(a), Self::Struct(b)) => Arc::ptr_eq(a, b) || instances_eq(a, b),
+            (Self::Iter(a), Self::Iter(b)) => a == b,
+            (Self::Error(a), Self::Error(b)) => a == b,
+            (Self::Result(a), Self::Result(b)) => a == b,
+            (Self::Module(a), Self::Module(b)) => a == b,
+            (Self::Nil, Self::Nil) | (Self::Unit, Self::Unit) => true,
+            _ => false,
+        }
+    }
+}
+
+thread_local! {
+    /// The pairs of instances being compared further up the stack of the current thread.
+    static COMPARING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(vec![]) };
+}
+
+/// Compares the fields of two instances, which may refer to each other or themselves. A pair
+/// that's already being compared is assumed to be equal, so that cycles end, and the fields are
+/// copied out first, since comparing them may lock the same instances again.
+fn instances_eq(a: &Arc<Mutex<Instance>>, b: &Arc<Mutex<Instance>>) -> bool {
+    let pair = (Arc::as_ptr(a) as usize, Arc::as_ptr(b) as usize);
+    if COMPARING.with(|c| c.borrow().contains(&pair)) {
+        return true;
+    }
+
+    let a = a.lock().unwrap().clone();
+    let b = b.lock().unwrap().clone();
+
+    COMPARING.with(|c| c.borrow_mut().push(pair));
+    let eq = a == b;
+    COMPARING.with(|c| c.borrow_mut().pop());
+
+    eq
+}
+
 impl Val {
     pub fn get_type(&self) -> &'static str {
         match self {
@@ -13,6 +80,36 @@ impl Val {
             Self::Unit => "()",
             Self::Real(_) => "Real number",
             Self::Bool(_) => "Boolean",
+            Self::List(_) => "List",
+            Self::Map(_) => "Map",
+            Self::Range(_) => "Range",
+            Self::Func(_) => "Function",
+            Self::Struct(_) => "Struct",
+            Self::Iter(_) => "Iterator",
+            Self::Error(_) => "Error",
+            Self::Result(_) => "Result",
+            Self::Module(_) => "Module",
+            Self::Nil => "nil",
         }
     }
+
+    /// Returns an iterator over the elements of `self`: the items of a list, the characters of a
+    /// string, the `[key, value]` entries of a map, or the elements of a range.
+    pub fn iterate(self) -> Result<Box<dyn Iterator<Item = Val> + Send>, EvalError> {
+        Ok(match self {
+            Self::List(items) => Box::new(items.into_iter()),
+            Self::Str(s) => Box::new(
+                s.chars()
+                    .map(|c| Val::Str(c.into()))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
+            Self::Map(map) => Box::new(
+                map.into_iter()
+                    .map(|(k, v)| Val::List(vec![Val::Str(k), v])),
+            ),
+            Self::Range(range) => Box::new(range.iter()?),
+            v => return Err(EvalError::NotIterable(v.get_type().into())),
+        })
+    }
 }